@@ -11,3 +11,6 @@ pub const USER_SEED: &[u8] = b"user";
 
 #[constant]
 pub const POST_SEED: &[u8] = b"post"; // fixed: use "post" for post accounts
+
+#[constant]
+pub const FOLLOW_SEED: &[u8] = b"follow";
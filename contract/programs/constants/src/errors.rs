@@ -47,7 +47,133 @@ pub enum VoteError {
     #[msg("Poll not currently active")]
     PollNotActive,
     #[msg("Poll does not exist or not found")]
-    PollDoesNotExist
+    PollDoesNotExist,
+    #[msg("Registration deadline must be before the poll starts")]
+    InvalidRegistrationDeadline,
+    #[msg("Candidate registration is closed for this poll")]
+    RegistrationClosed,
+    #[msg("Caller is not allowed to register candidates in this poll")]
+    RegistrationNotAllowed,
+    #[msg("Only the poll creator can perform this action")]
+    NotPollCreator,
+    #[msg("Token-weighted polls require the voter's token account")]
+    TokenAccountRequired,
+    #[msg("Token account does not match the poll mint or the voter")]
+    InvalidTokenAccount,
+    #[msg("Voter has no voting power in this poll")]
+    NoVotingPower,
+    #[msg("Escrowed polls require the vote escrow account")]
+    EscrowRequired,
+    #[msg("Poll has not ended yet")]
+    PollNotEnded,
+    #[msg("Instruction does not match the poll's voting mode")]
+    WrongPollMode,
+    #[msg("Poll has reached its maximum number of candidates")]
+    TooManyCandidates,
+    #[msg("Ballot is empty, too long, or lists an invalid or repeated candidate")]
+    InvalidBallot,
+    #[msg("Voter account does not belong to this poll")]
+    InvalidVoterAccount,
+    #[msg("Ranked-choice tally has already finished")]
+    TallyFinished,
+    #[msg("Poll mode settings are invalid")]
+    InvalidPollMode,
+    #[msg("Candidate account does not match the ballot")]
+    InvalidCandidateAccount,
+    #[msg("Not enough voice credits for this allocation")]
+    InsufficientCredits,
+    #[msg("Reveal window is not open")]
+    RevealNotOpen,
+    #[msg("Vote has already been revealed")]
+    AlreadyRevealed,
+    #[msg("Revealed vote does not match the commitment")]
+    CommitmentMismatch,
+    #[msg("Voter is not in the poll's allowlist")]
+    NotEligible,
+    #[msg("Poll has already started")]
+    PollAlreadyStarted,
+    #[msg("Poll has already been finalized")]
+    PollAlreadyFinalized,
+    #[msg("Every candidate of the poll must be passed exactly once")]
+    IncompleteCandidates,
+    #[msg("Ranked-choice tally has not finished")]
+    TallyNotFinished,
+    #[msg("Vote is already cast for this candidate")]
+    SameCandidate,
+    #[msg("Escrowed votes stay locked until the poll ends")]
+    VoteLocked,
+    #[msg("Cannot delegate a vote to yourself")]
+    SelfDelegation,
+    #[msg("Delegation account is invalid for this vote")]
+    InvalidDelegation,
+    #[msg("Delegations only count in open single-choice polls")]
+    DelegationNotAllowed,
+    #[msg("Vote was not cast by a delegate")]
+    NotDelegated,
+    #[msg("Delegated votes must be overridden by the delegator")]
+    DelegatedVote,
+    #[msg("Votes carrying delegations cannot be revoked")]
+    VoteHasDelegations,
+    #[msg("Quorum or pass threshold is out of range")]
+    InvalidPollRules,
+    #[msg("Poll must be finalized and past its grace period")]
+    PollNotClosable,
+    #[msg("All candidate accounts must be closed first")]
+    CandidatesStillOpen,
+    #[msg("Poll has been cancelled")]
+    PollCancelled,
+    #[msg("New end must be later and within the maximum extension")]
+    InvalidExtension,
+    #[msg("Survey question is empty, too long, or has too many options")]
+    InvalidQuestion,
+    #[msg("Survey already has the maximum number of questions")]
+    TooManyQuestions,
+    #[msg("Answers do not match the survey questions")]
+    InvalidAnswers,
+    #[msg("This poll only accepts votes through vote_with_nft")]
+    NftRequired,
+    #[msg("NFT is not held by the voter or is not in the poll's verified collection")]
+    InvalidNft,
+    #[msg("A poll can be gated by an allowlist or an NFT collection, not both")]
+    ConflictingVoterGate,
+    #[msg("Invalid conviction voting parameters")]
+    InvalidConvictionParams,
+    #[msg("Funding proposal has already been executed")]
+    ProposalExecuted,
+    #[msg("Funding proposal has not reached its conviction threshold")]
+    ConvictionBelowThreshold,
+    #[msg("Invalid stake amount")]
+    InvalidStakeAmount,
+    #[msg("Candidates have already registered for this poll")]
+    CandidatesRegistered,
+    #[msg("Only the candidate can do this")]
+    NotCandidateAuthority,
+    #[msg("Poll has not been finalized")]
+    PollNotFinalized,
+    #[msg("Candidate has no nomination deposit to settle")]
+    NoDeposit,
+    #[msg("Nomination deposit must be settled first")]
+    DepositNotSettled,
+    #[msg("Invalid proposal action")]
+    InvalidProposalAction,
+    #[msg("Poll does not carry a proposal action")]
+    NotAProposal,
+    #[msg("Proposal did not pass")]
+    ProposalNotPassed,
+    #[msg("Proposal timelock has not elapsed")]
+    TimelockActive,
+    #[msg("Proposal action has already been executed")]
+    ActionExecuted,
+    #[msg("Only platform or governance-token polls with a quorum can carry platform actions")]
+    NotGovernancePoll,
+    #[msg("Candidate name cannot be longer than 32 chars")]
+    CandidateNameTooLong,
+    #[msg("Candidate bio cannot be longer than 280 chars")]
+    CandidateBioTooLong,
+    #[msg("Candidate image URI cannot be longer than 200 chars")]
+    CandidateImageUriTooLong,
+    #[msg("Description cannot be longer than 280 chars")]
+    DescriptionTooLong,
 }
 
 //
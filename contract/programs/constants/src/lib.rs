@@ -1,5 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{program::invoke, system_instruction};
+use anchor_lang::solana_program::{hash::hashv, program::invoke, system_instruction};
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
 
 pub mod constants;
 pub mod states;
@@ -10,6 +14,28 @@ use crate::{constants::*, states::*, errors::*};
 declare_id!("73KCAwnfEwU7LPX7Ri2FXHvp1NZtCyRUc6EJVvm59oEs");
 // constants
 pub const ANCHOR_DISCRIMINATOR_SIZE: usize = 8;
+pub const MAX_BALLOT_LEN: usize = 16;
+// size of the per-poll `PollTally` scoreboard
+pub const MAX_POLL_CANDIDATES: usize = 64;
+pub const MAX_SURVEY_QUESTIONS: usize = 8;
+// survey answers are option bitmasks, one u8 per question
+pub const MAX_SURVEY_OPTIONS: usize = 8;
+// time after a poll closes before its accounts can be closed for rent
+pub const POLL_CLOSE_GRACE_PERIOD: u64 = 7 * 24 * 60 * 60;
+// total time `extend_poll` may add to a poll's end date
+pub const MAX_POLL_EXTENSION: u64 = 30 * 24 * 60 * 60;
+// milestones a campaign can split its goal into
+pub const MAX_MILESTONES: usize = 5;
+// reward tiers a campaign can offer
+pub const MAX_REWARD_TIERS: usize = 5;
+// how long donors have to vote on a milestone
+pub const MILESTONE_VOTING_PERIOD: u64 = 7 * 24 * 60 * 60;
+// share of the raised amount, in basis points, that must vote on a milestone
+pub const MILESTONE_QUORUM_BPS: u64 = 2_000;
+// delay between a proposal poll passing and its action becoming executable
+pub const PROPOSAL_TIMELOCK: u64 = 2 * 24 * 60 * 60;
+// fixed-point scale of conviction voting ratios
+pub const CONVICTION_SCALE: u64 = 1_000_000_000;
 
 #[program]
 pub mod godec_dapp {
@@ -123,23 +149,40 @@ pub mod godec_dapp {
 pub fn initialize_vote(ctx: Context<InitializeVote>) -> Result<()> {
         let counter = &mut ctx.accounts.counter;
         counter.count = 0;
-
-        let registerations = &mut ctx.accounts.registerations;
-        registerations.count = 0;
         Ok(())
     }
 
+   #[allow(clippy::too_many_arguments)]
    pub fn create_poll(
     ctx: Context<CreatePoll>,
     poll_id: u64, // The ID is now an instruction argument
     description: String,
     start: u64,
     end: u64,
+    registration_policy: RegistrationPolicy,
+    registration_end: u64,
+    mode: PollMode,
 ) -> Result<()> {
     if start >= end {
         return Err(errors::VoteError::InvalidDates.into());
     }
 
+    if registration_end >= start {
+        return Err(errors::VoteError::InvalidRegistrationDeadline.into());
+    }
+
+    let valid_mode = match mode {
+        PollMode::Approval { max_approvals } => {
+            max_approvals > 0 && max_approvals as usize <= MAX_BALLOT_LEN
+        }
+        PollMode::Quadratic { credits } => credits > 0,
+        PollMode::CommitReveal { reveal_end } => reveal_end > end,
+        _ => true,
+    };
+    if !valid_mode {
+        return Err(errors::VoteError::InvalidPollMode.into());
+    }
+
     let counter = &mut ctx.accounts.counter;
     if counter.count + 1 != poll_id {
         return Err(errors::VoteError::PollCounterUnderflow.into());
@@ -154,560 +197,4916 @@ pub fn initialize_vote(ctx: Context<InitializeVote>) -> Result<()> {
     poll.end = end;
     poll.candidates = 0;
   poll.creator = ctx.accounts.user.key();
+    poll.registration_policy = registration_policy;
+    poll.registration_end = registration_end;
+    poll.mode = mode;
+    poll.outcome = PollOutcome::Pending;
+
+    let mut tally = ctx.accounts.tally.load_init()?;
+    tally.poll_id = poll_id;
 
  Ok(())
 } 
 
-     pub fn register_candidate(
-        ctx: Context<RegisterCandidate>,
-        poll_id: u64,
-        name: String,
-    ) -> Result<()> {
-        let poll = &mut ctx.accounts.poll;
-        if poll.id != poll_id {
-            return Err(errors::VoteError::PollDoesNotExist.into());
+    pub fn allow_candidate(ctx: Context<AllowCandidate>, poll_id: u64, wallet: Pubkey) -> Result<()> {
+        let poll = &ctx.accounts.poll;
+        if poll.creator != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotPollCreator.into());
         }
 
-        let candidate = &mut ctx.accounts.candidate;
-        if candidate.has_registered {
-            return Err(errors::VoteError::CandidateAlreadyRegistered.into());
+        if poll.registration_policy != RegistrationPolicy::Allowlist {
+            return Err(errors::VoteError::RegistrationNotAllowed.into());
         }
 
-        let registerations = &mut ctx.accounts.registerations;
-        registerations.count += 1;
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp > poll.registration_end {
+            return Err(errors::VoteError::RegistrationClosed.into());
+        }
 
-        candidate.has_registered = true;
-        candidate.cid = registerations.count;
-        candidate.poll_id = poll_id;
-        candidate.name = name;
+        let pass = &mut ctx.accounts.registration_pass;
+        pass.poll_id = poll_id;
+        pass.wallet = wallet;
+        pass.used = false;
 
         Ok(())
     }
 
- pub fn vote(ctx: Context<Vote>, poll_id: u64, cid: u64) -> Result<()> {
-        let voter = &mut ctx.accounts.voter;
-        let candidate = &mut ctx.accounts.candidate;
+    // restrict voting to the wallets in a Merkle tree; only before voting starts
+    pub fn set_voter_root(
+        ctx: Context<SetVoterRoot>,
+        _poll_id: u64,
+        voter_root: Option<[u8; 32]>,
+        weighted_root: bool,
+    ) -> Result<()> {
         let poll = &mut ctx.accounts.poll;
-
-        if !candidate.has_registered || candidate.poll_id != poll_id {
-            return Err(errors::VoteError::CandidateNotRegistered.into());
-        }
-
-        if voter.has_voted {
-            return Err(errors::VoteError::VoterAlreadyVoted.into());
+        if poll.creator != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotPollCreator.into());
         }
 
         let current_timestamp = Clock::get()?.unix_timestamp as u64;
-        if current_timestamp < poll.start || current_timestamp > poll.end {
-            return Err(errors::VoteError::PollNotActive.into());
+        if current_timestamp >= poll.start {
+            return Err(errors::VoteError::PollAlreadyStarted.into());
         }
 
-        voter.poll_id = poll_id;
-        voter.cid = cid;
-        voter.has_voted = true;
+        if voter_root.is_some() && poll.nft_collection.is_some() {
+            return Err(errors::VoteError::ConflictingVoterGate.into());
+        }
 
-        candidate.votes += 1;
+        poll.voter_root = voter_root;
+        poll.weighted_root = weighted_root;
 
         Ok(())
     }
 
+    // restrict voting to holders of NFTs from a verified collection, one vote per NFT
+    pub fn set_nft_collection(
+        ctx: Context<SetNftCollection>,
+        _poll_id: u64,
+        nft_collection: Option<Pubkey>,
+    ) -> Result<()> {
+        let poll = &mut ctx.accounts.poll;
+        if poll.creator != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotPollCreator.into());
+        }
 
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp >= poll.start {
+            return Err(errors::VoteError::PollAlreadyStarted.into());
+        }
 
+        if poll.mode != PollMode::Standard {
+            return Err(errors::VoteError::WrongPollMode.into());
+        }
 
-    // ==============================
-    // Crowdfunding
-    // ==============================
-
-    // program setup
-    pub fn initialize(ctx: Context<InitializeCtx>) -> Result<()> {
-        let state = &mut ctx.accounts.program_state;
-        let deployer = &ctx.accounts.deployer;
-
-        if state.initialized {
-            msg!("Program already initialized");
-            return Err(ErrorCode::AlreadyInitialized.into());
+        if nft_collection.is_some() && (poll.voter_root.is_some() || poll.audience != PollAudience::Anyone) {
+            return Err(errors::VoteError::ConflictingVoterGate.into());
         }
 
-        state.campaign_count = 0;
-        state.platform_fee = 5;
-        state.platform_address = deployer.key();
-        state.initialized = true;
+        poll.nft_collection = nft_collection;
 
-        msg!("Program initialized successfully");
         Ok(())
     }
 
-    // create a campaign
-    pub fn create_campaign(
-        ctx: Context<CreateCampaignCtx>,
-        title: String,
-        description: String,
-        image_url: String,
-        goal: u64,
+    // quorum and pass threshold can only be changed before voting starts
+    pub fn set_poll_rules(
+        ctx: Context<SetPollRules>,
+        _poll_id: u64,
+        quorum: Option<Quorum>,
+        pass_threshold_bps: u16,
     ) -> Result<()> {
-        let campaign = &mut ctx.accounts.campaign;
-        let state = &mut ctx.accounts.program_state;
-
-        if title.len() > 64 {
-            msg!("Title length invalid");
-            return Err(ErrorCode::TitleTooLong.into());
-        }
-        if description.len() > 512 {
-            msg!("Description length invalid");
-            return Err(ErrorCode::DescriptionTooLong.into());
-        }
-        if image_url.len() > 256 {
-            msg!("Image URL length invalid");
-            return Err(ErrorCode::ImageUrlTooLong.into());
+        let poll = &mut ctx.accounts.poll;
+        if poll.creator != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotPollCreator.into());
         }
-        if goal < 1_000_000_000 {
-            msg!("Campaign goal too small");
-            return Err(ErrorCode::InvalidGoalAmount.into());
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp >= poll.start {
+            return Err(errors::VoteError::PollAlreadyStarted.into());
         }
 
-        state.campaign_count += 1;
+        let valid_quorum = match quorum {
+            Some(Quorum::Votes { min_votes }) => min_votes > 0,
+            Some(Quorum::Share { eligible, bps }) => eligible > 0 && bps > 0 && bps <= 10_000,
+            None => true,
+        };
+        if !valid_quorum || pass_threshold_bps > 10_000 {
+            return Err(errors::VoteError::InvalidPollRules.into());
+        }
 
-        campaign.cid = state.campaign_count;
-        campaign.creator = ctx.accounts.creator.key();
-        campaign.title = title;
-        campaign.description = description;
-        campaign.image_url = image_url;
-        campaign.goal = goal;
-        campaign.amount_raised = 0;
-        campaign.donors = 0;
-        campaign.withdrawals = 0;
-        campaign.timestamp = Clock::get()?.unix_timestamp as u64;
-        campaign.active = true;
-        campaign.balance = 0;
+        poll.quorum = quorum;
+        poll.pass_threshold_bps = pass_threshold_bps;
 
-        msg!("New campaign successfully created");
         Ok(())
     }
 
-    // edit campaign details
-    pub fn update_campaign(
-        ctx: Context<UpdateCampaignCtx>,
-        cid: u64,
-        title: String,
-        description: String,
-        image_url: String,
-        goal: u64,
+    // deposit every candidate posts on registration, refunded at `min_share_bps` of the votes
+    pub fn set_nomination_deposit(
+        ctx: Context<SetNominationDeposit>,
+        _poll_id: u64,
+        deposit: u64,
+        min_share_bps: u16,
     ) -> Result<()> {
-        let campaign = &mut ctx.accounts.campaign;
-        let creator = &mut ctx.accounts.creator;
-
-        if campaign.creator != creator.key() {
-            msg!("Unauthorized attempt to update");
-            return Err(ErrorCode::Unauthorized.into());
+        let poll = &mut ctx.accounts.poll;
+        if poll.creator != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotPollCreator.into());
         }
 
-        if campaign.cid != cid {
-            msg!("Campaign ID mismatch");
-            return Err(ErrorCode::CampaignNotFound.into());
+        if poll.candidates > 0 {
+            return Err(errors::VoteError::CandidatesRegistered.into());
         }
 
-        if title.len() > 64 {
-            msg!("Title exceeds limit");
-            return Err(ErrorCode::TitleTooLong.into());
-        }
-        if description.len() > 512 {
-            msg!("Description exceeds limit");
-            return Err(ErrorCode::DescriptionTooLong.into());
-        }
-        if image_url.len() > 256 {
-            msg!("Image URL exceeds limit");
-            return Err(ErrorCode::ImageUrlTooLong.into());
-        }
-        if goal < 1_000_000_000 {
-            msg!("Goal is too small");
-            return Err(ErrorCode::InvalidGoalAmount.into());
+        if min_share_bps > 10_000 {
+            return Err(errors::VoteError::InvalidPollRules.into());
         }
 
-        campaign.title = title;
-        campaign.description = description;
-        campaign.image_url = image_url;
-        campaign.goal = goal;
+        poll.nomination_deposit = deposit;
+        poll.deposit_min_share_bps = min_share_bps;
 
-        msg!("Campaign updated successfully");
         Ok(())
     }
 
-    // deactivate campaign
-    pub fn delete_campaign(ctx: Context<DeleteCampaignCtx>, cid: u64) -> Result<()> {
-        let campaign = &mut ctx.accounts.campaign;
-        let creator = &mut ctx.accounts.creator;
+    // Turns the poll into a governance proposal: if candidate `action_cid` wins,
+    // `execute_proposal` applies the action once the timelock has elapsed.
+    pub fn set_proposal_action(
+        ctx: Context<SetProposalAction>,
+        _poll_id: u64,
+        action: ProposalAction,
+        action_cid: u64,
+    ) -> Result<()> {
+        let poll = &mut ctx.accounts.poll;
+        if poll.creator != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotPollCreator.into());
+        }
 
-        if campaign.creator != creator.key() {
-            msg!("Unauthorized attempt to deactivate");
-            return Err(ErrorCode::Unauthorized.into());
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp >= poll.start {
+            return Err(errors::VoteError::PollAlreadyStarted.into());
         }
 
-        if campaign.cid != cid {
-            msg!("Campaign not found");
-            return Err(ErrorCode::CampaignNotFound.into());
+        let supply = governance_supply(&ctx.accounts.program_state, &ctx.accounts.governance_mint);
+        if !poll.can_carry_platform_action(&ctx.accounts.program_state, supply) {
+            return Err(errors::VoteError::NotGovernancePoll.into());
         }
 
-        if !campaign.active {
-            msg!("Campaign already inactive");
-            return Err(ErrorCode::InactiveCampaign.into());
+        let valid_action = match action {
+            ProposalAction::SetPlatformFee { fee } => (1..=15).contains(&fee),
+            ProposalAction::SetPlatformAddress { address } => address != Pubkey::default(),
+            ProposalAction::SetModulePaused { .. } => true,
+        };
+        if !valid_action || action_cid == 0 {
+            return Err(errors::VoteError::InvalidProposalAction.into());
         }
 
-        campaign.active = false;
+        poll.action = Some(action);
+        poll.action_cid = action_cid;
 
-        msg!("Campaign successfully deactivated");
         Ok(())
     }
 
-    // donate to campaign
-    pub fn donate(ctx: Context<DonateCtx>, cid: u64, amount: u64) -> Result<()> {
-        let campaign = &mut ctx.accounts.campaign;
-        let donor = &mut ctx.accounts.donor;
-        let transaction = &mut ctx.accounts.transaction;
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>, _poll_id: u64) -> Result<()> {
+        let supply = governance_supply(&ctx.accounts.program_state, &ctx.accounts.governance_mint);
+        let poll = &mut ctx.accounts.poll;
+        let state = &mut ctx.accounts.program_state;
 
-        if campaign.cid != cid {
-            msg!("Campaign does not exist for donation");
-            return Err(ErrorCode::CampaignNotFound.into());
+        let action = match poll.action {
+            Some(action) => action,
+            None => return Err(errors::VoteError::NotAProposal.into()),
+        };
+
+        if poll.action_executed {
+            return Err(errors::VoteError::ActionExecuted.into());
         }
 
-        if !campaign.active {
-            msg!("Inactive campaign — donation rejected");
-            return Err(ErrorCode::InactiveCampaign.into());
+        // the rules may have changed since the action was attached
+        if !poll.can_carry_platform_action(state, supply) {
+            return Err(errors::VoteError::NotGovernancePoll.into());
         }
 
-        if amount < 1_000_000 {
-            msg!("Donation is less than 0.0001 SOL");
-            return Err(ErrorCode::InvalidDonationAmount.into());
+        if poll.outcome != PollOutcome::Passed || poll.winners != [poll.action_cid] {
+            return Err(errors::VoteError::ProposalNotPassed.into());
         }
 
-        if campaign.amount_raised >= campaign.goal {
-            msg!("Target already achieved");
-            return Err(ErrorCode::CampaignGoalActualized.into());
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp < poll.finalized_at + PROPOSAL_TIMELOCK {
+            return Err(errors::VoteError::TimelockActive.into());
         }
 
-        let tx_instruction = system_instruction::transfer(
-            &donor.key(),
-            &campaign.key(),
-            amount,
-        );
+        match action {
+            ProposalAction::SetPlatformFee { fee } => state.platform_fee = fee,
+            ProposalAction::SetPlatformAddress { address } => state.platform_address = address,
+            ProposalAction::SetModulePaused { module, paused } => match module {
+                PlatformModule::Crowdfunding => state.crowdfunding_paused = paused,
+                PlatformModule::Blog => state.blog_paused = paused,
+            },
+        }
+        poll.action_executed = true;
 
-        let result = invoke(
-            &tx_instruction,
-            &[donor.to_account_info(), campaign.to_account_info()],
-        );
+        Ok(())
+    }
 
-        if let Err(_e) = result {
-            msg!("Failed to transfer donation");
-            return Err(ErrorCode::InsufficientFund.into());
+    // Attaches a poll to one of the caller's posts, optionally limiting voting
+    // to wallets that follow the caller.
+    pub fn attach_poll_to_post(
+        ctx: Context<AttachPollToPost>,
+        _title: String,
+        _poll_id: u64,
+        followers_only: bool,
+    ) -> Result<()> {
+        let poll = &mut ctx.accounts.poll;
+        let author = ctx.accounts.authority.key();
+        if poll.creator != author {
+            return Err(errors::VoteError::NotPollCreator.into());
         }
 
-        campaign.amount_raised += amount;
-        campaign.balance += amount;
-        campaign.donors += 1;
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp >= poll.start {
+            return Err(errors::VoteError::PollAlreadyStarted.into());
+        }
 
-        transaction.amount = amount;
-        transaction.cid = cid;
-        transaction.owner = donor.key();
-        transaction.timestamp = Clock::get()?.unix_timestamp as u64;
-        transaction.credited = true;
+        if followers_only {
+            if poll.nft_collection.is_some() {
+                return Err(errors::VoteError::ConflictingVoterGate.into());
+            }
+            poll.audience = PollAudience::Followers { author };
+        }
+
+        ctx.accounts.post_account.poll = Some(poll.key());
 
-        msg!("Donation processed successfully");
         Ok(())
     }
 
-     // withdraw funds from a campaign
-    pub fn withdraw(ctx: Context<WithdrawCtx>, cid: u64, amount: u64) -> Result<()> {
+    // Attaches a poll to one of the caller's campaigns, optionally limiting
+    // voting to wallets that donated to it.
+    pub fn attach_poll_to_campaign(
+        ctx: Context<AttachPollToCampaign>,
+        cid: u64,
+        _poll_id: u64,
+        donors_only: bool,
+    ) -> Result<()> {
+        let poll = &mut ctx.accounts.poll;
         let campaign = &mut ctx.accounts.campaign;
-        let creator = &ctx.accounts.creator;
-        let transaction = &mut ctx.accounts.transaction;
-        let state = &mut ctx.accounts.program_state;
-        let platform_account_info = &ctx.accounts.platform_address;
+        let creator = ctx.accounts.creator.key();
+        if campaign.creator != creator {
+            msg!("Unauthorized poll attachment");
+            return Err(ErrorCode::Unauthorized.into());
+        }
 
-        if campaign.cid != cid {
-            msg!("Campaign not found for withdrawal");
-            return Err(ErrorCode::CampaignNotFound.into());
+        if poll.creator != creator {
+            return Err(errors::VoteError::NotPollCreator.into());
         }
 
-        if campaign.creator != creator.key() {
-            msg!("Unauthorized withdrawal attempt");
-            return Err(ErrorCode::Unauthorized.into());
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp >= poll.start {
+            return Err(errors::VoteError::PollAlreadyStarted.into());
         }
 
-        //fixing the amount such that  less than 1 sol cant be deducted
-        if amount < 1_000_000_000 {
-            msg!("Withdrawal amount too low");
-            return Err(ErrorCode::InvalidWithdrawalAmount.into());
+        if donors_only {
+            if poll.nft_collection.is_some() {
+                return Err(errors::VoteError::ConflictingVoterGate.into());
+            }
+            poll.audience = PollAudience::Donors { cid };
         }
 
-        if amount > campaign.balance {
-            msg!("Withdrawal exceeds campaign balance");
-            return Err(ErrorCode::CampaignGoalActualized.into());
+        campaign.poll = Some(poll.key());
+
+        Ok(())
+    }
+
+    pub fn cancel_poll(ctx: Context<CancelPoll>, _poll_id: u64) -> Result<()> {
+        let poll = &mut ctx.accounts.poll;
+        if poll.creator != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotPollCreator.into());
         }
 
-        if platform_account_info.key() != state.platform_address {
-            msg!("Invalid platform address for withdrawal");
-            return Err(ErrorCode::InvalidPlatformAddress.into());
+        if poll.cancelled {
+            return Err(errors::VoteError::PollCancelled.into());
         }
 
-        let rent_balance = Rent::get()?.minimum_balance(campaign.to_account_info().data_len());
-        if amount > **campaign.to_account_info().lamports.borrow() - rent_balance {
-            msg!("Withdrawal exceeds campaign's usable balance");
-            return Err(ErrorCode::InsufficientFund.into());
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp > poll.end {
+            return Err(errors::VoteError::PollNotActive.into());
         }
 
-        let platform_fee = amount * state.platform_fee / 100;
-        let creator_amount = amount - platform_fee;
+        poll.cancelled = true;
+        poll.outcome = PollOutcome::Cancelled;
 
-        **campaign.to_account_info().try_borrow_mut_lamports()? -= creator_amount;
-        **creator.to_account_info().try_borrow_mut_lamports()? += creator_amount;
+        Ok(())
+    }
 
-        **campaign.to_account_info().try_borrow_mut_lamports()? -= platform_fee;
-        **platform_account_info.to_account_info().try_borrow_mut_lamports()? += platform_fee;
+    // push the end date back while voting is open, up to MAX_POLL_EXTENSION in total
+    pub fn extend_poll(ctx: Context<ExtendPoll>, _poll_id: u64, new_end: u64) -> Result<()> {
+        let poll = &mut ctx.accounts.poll;
+        if poll.creator != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotPollCreator.into());
+        }
 
-        campaign.withdrawals += 1;
-        campaign.balance -= amount;
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_open(current_timestamp) {
+            return Err(errors::VoteError::PollNotActive.into());
+        }
 
-        transaction.amount = amount;
-        transaction.cid = cid;
-        transaction.owner = creator.key();
-        transaction.timestamp = Clock::get()?.unix_timestamp as u64;
-        transaction.credited = false;
+        if new_end <= poll.end || poll.extended + (new_end - poll.end) > MAX_POLL_EXTENSION {
+            return Err(errors::VoteError::InvalidExtension.into());
+        }
+
+        let extension = new_end - poll.end;
+        // the reveal window keeps its length
+        if let PollMode::CommitReveal { reveal_end } = poll.mode {
+            poll.mode = PollMode::CommitReveal { reveal_end: reveal_end + extension };
+        }
+        poll.extended += extension;
+        poll.end = new_end;
 
-        msg!("Withdrawal successful");
         Ok(())
     }
 
-    // update platform settings
-    pub fn update_platform_settings(
-        ctx: Context<UpdatePlatformSettingsCtx>,
-        new_platform_fee: u64,
+     pub fn register_candidate(
+        ctx: Context<RegisterCandidate>,
+        poll_id: u64,
+        name: String,
     ) -> Result<()> {
-        let state = &mut ctx.accounts.program_state;
-        let updater = &ctx.accounts.updater;
+        let poll = &mut ctx.accounts.poll;
+        if poll.id != poll_id {
+            return Err(errors::VoteError::PollDoesNotExist.into());
+        }
 
-        if updater.key() != state.platform_address {
-            msg!("Unauthorized update attempt");
-            return Err(ErrorCode::Unauthorized.into());
+        if name.len() > 32 {
+            return Err(errors::VoteError::CandidateNameTooLong.into());
         }
 
-        if !(1..=15).contains(&new_platform_fee) {
-            msg!("Fee percentage not within range");
-            return Err(ErrorCode::InvalidPlatformFee.into());
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp > poll.registration_end {
+            return Err(errors::VoteError::RegistrationClosed.into());
         }
 
-        state.platform_fee = new_platform_fee;
+        let user = ctx.accounts.user.key();
+        match poll.registration_policy {
+            RegistrationPolicy::CreatorOnly => {
+                if user != poll.creator {
+                    return Err(errors::VoteError::RegistrationNotAllowed.into());
+                }
+            }
+            RegistrationPolicy::Open => {}
+            RegistrationPolicy::Allowlist => {
+                let pass = match ctx.accounts.registration_pass.as_mut() {
+                    Some(pass) => pass,
+                    None => return Err(errors::VoteError::RegistrationNotAllowed.into()),
+                };
+                if pass.used {
+                    return Err(errors::VoteError::RegistrationNotAllowed.into());
+                }
+                pass.used = true;
+            }
+        }
+
+        if poll.mode == PollMode::Survey {
+            return Err(errors::VoteError::WrongPollMode.into());
+        }
+
+        if poll.mode == PollMode::RankedChoice && poll.candidates as usize >= MAX_BALLOT_LEN {
+            return Err(errors::VoteError::TooManyCandidates.into());
+        }
+
+        if poll.candidates as usize >= MAX_POLL_CANDIDATES {
+            return Err(errors::VoteError::TooManyCandidates.into());
+        }
+
+        let candidate = &mut ctx.accounts.candidate;
+        if candidate.has_registered {
+            return Err(errors::VoteError::CandidateAlreadyRegistered.into());
+        }
+
+        if poll.nomination_deposit > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: candidate.to_account_info(),
+                    },
+                ),
+                poll.nomination_deposit,
+            )?;
+        }
+
+        poll.candidates += 1;
+
+        candidate.has_registered = true;
+        candidate.cid = poll.candidates;
+        candidate.poll_id = poll_id;
+        candidate.name = name;
+        candidate.authority = user;
+        candidate.deposit = poll.nomination_deposit;
 
-        msg!("Platform settings updated");
         Ok(())
     }
 
+    // Pulls out of a poll before voting starts and refunds the deposit. The
+    // candidate account stays so cids remain contiguous for finalization.
+    pub fn withdraw_candidacy(ctx: Context<WithdrawCandidacy>, _poll_id: u64, _cid: u64) -> Result<()> {
+        let poll = &ctx.accounts.poll;
+        let candidate = &mut ctx.accounts.candidate;
 
-    // ==============================
-    // Blog Site
-    // ==============================
+        if candidate.authority != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotCandidateAuthority.into());
+        }
 
- pub fn init_user(ctx: Context<InitUser>, name: String, avatar: String) -> Result<()> {
-        let user_account = &mut ctx.accounts.user_account;
-        let authority = &ctx.accounts.authority;
+        if !candidate.has_registered {
+            return Err(errors::VoteError::CandidateNotRegistered.into());
+        }
 
-        user_account.name = name;
-        user_account.avatar = avatar;
-        user_account.last_post_id = 0;
-        user_account.post_count = 0;
-        user_account.authority = authority.key();
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp >= poll.start {
+            return Err(errors::VoteError::PollAlreadyStarted.into());
+        }
+
+        // ranked ballots refer to candidates by position and cannot skip one
+        if poll.mode == PollMode::RankedChoice {
+            return Err(errors::VoteError::WrongPollMode.into());
+        }
+
+        let deposit = candidate.deposit;
+        **candidate.to_account_info().try_borrow_mut_lamports()? -= deposit;
+        **ctx.accounts.user.to_account_info().try_borrow_mut_lamports()? += deposit;
+
+        candidate.deposit = 0;
+        candidate.has_registered = false;
+        candidate.withdrawn = true;
 
         Ok(())
     }
 
-  pub fn create_post(
-    ctx: Context<CreatePost>,
-    title: String,
-    content: String,
-    image_url: String,
-) -> Result<()> {
-    let post_account = &mut ctx.accounts.post_account;
-    let user_account = &mut ctx.accounts.user_account;
-    let authority = &ctx.accounts.authority;
+    pub fn update_candidate_profile(
+        ctx: Context<UpdateCandidateProfile>,
+        _poll_id: u64,
+        _cid: u64,
+        name: String,
+        bio: String,
+        image_uri: String,
+    ) -> Result<()> {
+        let poll = &ctx.accounts.poll;
+        let candidate = &mut ctx.accounts.candidate;
 
-    post_account.id = user_account.last_post_id;
-    post_account.title = title.clone();
-    post_account.content = content;
-    post_account.image_url = image_url;
-    post_account.user = user_account.key();
-    post_account.authority = authority.key();
+        if candidate.authority != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotCandidateAuthority.into());
+        }
 
-    // Update user stats
-    user_account.last_post_id = user_account
-        .last_post_id
-        .checked_add(1)
-        .unwrap();
+        if !candidate.has_registered {
+            return Err(errors::VoteError::CandidateNotRegistered.into());
+        }
+
+        if name.len() > 32 {
+            return Err(errors::VoteError::CandidateNameTooLong.into());
+        }
+        if bio.len() > 280 {
+            return Err(errors::VoteError::CandidateBioTooLong.into());
+        }
+        if image_uri.len() > 200 {
+            return Err(errors::VoteError::CandidateImageUriTooLong.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp >= poll.start {
+            return Err(errors::VoteError::PollAlreadyStarted.into());
+        }
+
+        candidate.name = name;
+        candidate.bio = bio;
+        candidate.image_uri = image_uri;
+
+        Ok(())
+    }
+
+    // Returns the deposit to the candidate if they reached the poll's minimum
+    // vote share, otherwise pays it to the creator. Cancelled polls refund it.
+    pub fn settle_nomination_deposit(
+        ctx: Context<SettleNominationDeposit>,
+        _poll_id: u64,
+        cid: u64,
+    ) -> Result<()> {
+        let poll = &ctx.accounts.poll;
+        let candidate = &mut ctx.accounts.candidate;
+
+        if candidate.deposit == 0 {
+            return Err(errors::VoteError::NoDeposit.into());
+        }
+
+        let refund = if poll.cancelled {
+            true
+        } else {
+            if !poll.finalized {
+                return Err(errors::VoteError::PollNotFinalized.into());
+            }
+
+            let votes = if poll.mode == PollMode::RankedChoice {
+                match ctx.accounts.ranked_results.as_ref().and_then(|r| r.rounds.first()) {
+                    Some(first_round) => first_round.tallies[(cid - 1) as usize],
+                    None => return Err(errors::VoteError::TallyNotFinished.into()),
+                }
+            } else {
+                candidate.votes
+            };
+            votes as u128 * 10_000 >= poll.total_votes as u128 * poll.deposit_min_share_bps as u128
+        };
+
+        let recipient = if refund {
+            ctx.accounts.authority.to_account_info()
+        } else {
+            ctx.accounts.creator.to_account_info()
+        };
+
+        let deposit = candidate.deposit;
+        **candidate.to_account_info().try_borrow_mut_lamports()? -= deposit;
+        **recipient.try_borrow_mut_lamports()? += deposit;
+        candidate.deposit = 0;
+
+        Ok(())
+    }
+
+    // Delegations held by the caller may be passed through remaining accounts as
+    // (delegation, delegator's voter PDA) pairs. Each one creates the delegator's
+    // voter account and adds its weight, unless the delegator already voted.
+    // Polls restricted to an allowlist or audience don't accept delegations.
+    pub fn vote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Vote<'info>>,
+        poll_id: u64,
+        cid: u64,
+        eligibility: Option<EligibilityProof>,
+    ) -> Result<()> {
+        let voter = &mut ctx.accounts.voter;
+        let candidate = &mut ctx.accounts.candidate;
+        let poll = &mut ctx.accounts.poll;
+
+        if !candidate.has_registered || candidate.poll_id != poll_id {
+            return Err(errors::VoteError::CandidateNotRegistered.into());
+        }
+
+        if voter.has_voted {
+            return Err(errors::VoteError::VoterAlreadyVoted.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_open(current_timestamp) {
+            return Err(errors::VoteError::PollNotActive.into());
+        }
+
+        let allowed_weight = check_eligibility(
+            poll,
+            &ctx.accounts.user.key(),
+            &eligibility,
+            ctx.accounts.audience_record.as_ref(),
+        )?;
+
+        let weight = match poll.mode {
+            PollMode::Standard => allowed_weight,
+            PollMode::TokenWeighted { mint, escrow } => {
+                let voter_token_account = match ctx.accounts.voter_token_account.as_ref() {
+                    Some(account) => account,
+                    None => return Err(errors::VoteError::TokenAccountRequired.into()),
+                };
+                if voter_token_account.mint != mint
+                    || voter_token_account.owner != ctx.accounts.user.key()
+                {
+                    return Err(errors::VoteError::InvalidTokenAccount.into());
+                }
+
+                let balance = voter_token_account.amount;
+                if balance == 0 {
+                    return Err(errors::VoteError::NoVotingPower.into());
+                }
+
+                if escrow {
+                    let (vote_escrow, token_program) = match (
+                        ctx.accounts.vote_escrow.as_ref(),
+                        ctx.accounts.token_program.as_ref(),
+                    ) {
+                        (Some(vote_escrow), Some(token_program)) => (vote_escrow, token_program),
+                        _ => return Err(errors::VoteError::EscrowRequired.into()),
+                    };
+
+                    token::transfer(
+                        CpiContext::new(
+                            token_program.to_account_info(),
+                            Transfer {
+                                from: voter_token_account.to_account_info(),
+                                to: vote_escrow.to_account_info(),
+                                authority: ctx.accounts.user.to_account_info(),
+                            },
+                        ),
+                        balance,
+                    )?;
+                }
+
+                balance
+            }
+            _ => return Err(errors::VoteError::WrongPollMode.into()),
+        };
+
+        let mut delegated_weight: u64 = 0;
+        if !ctx.remaining_accounts.is_empty() {
+            if poll.mode != PollMode::Standard
+                || poll.voter_root.is_some()
+                || poll.audience != PollAudience::Anyone
+            {
+                return Err(errors::VoteError::DelegationNotAllowed.into());
+            }
+            let pairs = ctx.remaining_accounts.chunks_exact(2);
+            if !pairs.remainder().is_empty() {
+                return Err(errors::VoteError::InvalidDelegation.into());
+            }
+
+            let user = ctx.accounts.user.key();
+            for pair in pairs {
+                let delegation = Account::<Delegation>::try_from(&pair[0])?;
+                let delegator_voter = &pair[1];
+
+                let in_scope = match delegation.scope {
+                    DelegationScope::Poll { poll_id: scope_poll_id } => scope_poll_id == poll_id,
+                    DelegationScope::Creator { creator } => creator == poll.creator,
+                };
+                if delegation.delegate != user || !in_scope {
+                    return Err(errors::VoteError::InvalidDelegation.into());
+                }
+
+                let poll_id_bytes = poll_id.to_le_bytes();
+                let (voter_address, voter_bump) = Pubkey::find_program_address(
+                    &[b"voter", poll_id_bytes.as_ref(), delegation.delegator.as_ref()],
+                    ctx.program_id,
+                );
+                if delegator_voter.key() != voter_address {
+                    return Err(errors::VoteError::InvalidDelegation.into());
+                }
+
+                // the delegator voted directly, or another delegation already covered them
+                if !delegator_voter.data_is_empty() {
+                    continue;
+                }
+
+                // what the delegator would carry voting themselves
+                let delegator_weight = check_eligibility(poll, &delegation.delegator, &None, None)?;
+
+                create_pda_account(
+                    &ctx.accounts.user.to_account_info(),
+                    delegator_voter,
+                    ANCHOR_DISCRIMINATOR_SIZE + Voter::INIT_SPACE,
+                    &[
+                        b"voter",
+                        poll_id_bytes.as_ref(),
+                        delegation.delegator.as_ref(),
+                        &[voter_bump],
+                    ],
+                    &ctx.accounts.system_program,
+                    ctx.program_id,
+                )?;
+
+                let delegator_record = Voter {
+                    cid,
+                    poll_id,
+                    has_voted: true,
+                    weight: delegator_weight,
+                    delegate: Some(user),
+                    rent_payer: user,
+                    ..Default::default()
+                };
+                let mut data = delegator_voter.try_borrow_mut_data()?;
+                delegator_record.try_serialize(&mut &mut data[..])?;
+
+                delegated_weight += delegator_weight;
+                poll.voters += 1;
+            }
+        }
+
+        voter.poll_id = poll_id;
+        voter.cid = cid;
+        voter.has_voted = true;
+        voter.rent_payer = ctx.accounts.user.key();
+        voter.weight = weight + delegated_weight;
+        voter.delegated_weight = delegated_weight;
+
+        candidate.votes += weight + delegated_weight;
+        ctx.accounts.tally.load_mut()?.add(cid, weight + delegated_weight);
+        poll.voters += 1;
+
+        Ok(())
+    }
+
+    // casts the single vote carried by one NFT of the poll's collection
+    pub fn vote_with_nft(ctx: Context<VoteWithNft>, poll_id: u64, cid: u64) -> Result<()> {
+        let receipt = &mut ctx.accounts.receipt;
+        let candidate = &mut ctx.accounts.candidate;
+        let poll = &mut ctx.accounts.poll;
+
+        let collection = match poll.nft_collection {
+            Some(collection) => collection,
+            None => return Err(errors::VoteError::WrongPollMode.into()),
+        };
+
+        if !candidate.has_registered || candidate.poll_id != poll_id {
+            return Err(errors::VoteError::CandidateNotRegistered.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_open(current_timestamp) {
+            return Err(errors::VoteError::PollNotActive.into());
+        }
+
+        let nft_mint = &ctx.accounts.nft_mint;
+        let nft_token_account = &ctx.accounts.nft_token_account;
+        let in_collection = match &ctx.accounts.nft_metadata.collection {
+            Some(c) => c.verified && c.key == collection,
+            None => false,
+        };
+        if nft_mint.decimals != 0
+            || nft_mint.supply != 1
+            || nft_token_account.amount != 1
+            || !in_collection
+        {
+            return Err(errors::VoteError::InvalidNft.into());
+        }
+
+        receipt.poll_id = poll_id;
+        receipt.mint = nft_mint.key();
+        receipt.voter = ctx.accounts.user.key();
+        receipt.cid = cid;
+
+        candidate.votes += 1;
+        ctx.accounts.tally.load_mut()?.add(cid, 1);
+        poll.voters += 1;
+
+        Ok(())
+    }
+
+    pub fn delegate_vote(ctx: Context<DelegateVote>, scope: DelegationScope, delegate: Pubkey) -> Result<()> {
+        let delegation = &mut ctx.accounts.delegation;
+        let user = ctx.accounts.user.key();
+
+        if delegate == user {
+            return Err(errors::VoteError::SelfDelegation.into());
+        }
+
+        delegation.delegator = user;
+        delegation.delegate = delegate;
+        delegation.scope = scope;
+
+        Ok(())
+    }
+
+    pub fn undelegate_vote(_ctx: Context<UndelegateVote>, _scope: DelegationScope) -> Result<()> {
+        Ok(())
+    }
+
+    // A delegator who wants to vote themselves after their delegate already
+    // voted for them: moves their weight out of the delegate's vote.
+    pub fn override_delegation(ctx: Context<OverrideDelegation>, poll_id: u64, cid: u64) -> Result<()> {
+        let voter = &mut ctx.accounts.voter;
+        let delegate_voter = &mut ctx.accounts.delegate_voter;
+        let poll = &ctx.accounts.poll;
+        let current_candidate = &mut ctx.accounts.current_candidate;
+        let new_candidate = &mut ctx.accounts.new_candidate;
+
+        if voter.delegate.is_none() {
+            return Err(errors::VoteError::NotDelegated.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_open(current_timestamp) {
+            return Err(errors::VoteError::PollNotActive.into());
+        }
+
+        if !new_candidate.has_registered || new_candidate.poll_id != poll_id {
+            return Err(errors::VoteError::CandidateNotRegistered.into());
+        }
+
+        // staying with the delegate's pick only takes the vote back; both
+        // candidate accounts are then the same one and left untouched
+        if cid != delegate_voter.cid {
+            current_candidate.votes -= voter.weight;
+            new_candidate.votes += voter.weight;
+
+            let mut tally = ctx.accounts.tally.load_mut()?;
+            tally.sub(delegate_voter.cid, voter.weight);
+            tally.add(cid, voter.weight);
+        }
+
+        delegate_voter.weight -= voter.weight;
+        delegate_voter.delegated_weight -= voter.weight;
+
+        voter.cid = cid;
+        voter.delegate = None;
+
+        Ok(())
+    }
+
+    pub fn vote_ranked(
+        ctx: Context<VoteRanked>,
+        poll_id: u64,
+        ranking: Vec<u64>,
+        eligibility: Option<EligibilityProof>,
+    ) -> Result<()> {
+        let voter = &mut ctx.accounts.voter;
+        let poll = &mut ctx.accounts.poll;
+
+        if poll.mode != PollMode::RankedChoice {
+            return Err(errors::VoteError::WrongPollMode.into());
+        }
+
+        if voter.has_voted {
+            return Err(errors::VoteError::VoterAlreadyVoted.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_open(current_timestamp) {
+            return Err(errors::VoteError::PollNotActive.into());
+        }
+
+        let allowed_weight = check_eligibility(
+            poll,
+            &ctx.accounts.user.key(),
+            &eligibility,
+            ctx.accounts.audience_record.as_ref(),
+        )?;
+
+        if ranking.is_empty() || ranking.len() as u64 > poll.candidates {
+            return Err(errors::VoteError::InvalidBallot.into());
+        }
+        for (i, cid) in ranking.iter().enumerate() {
+            if *cid == 0 || *cid > poll.candidates || ranking[..i].contains(cid) {
+                return Err(errors::VoteError::InvalidBallot.into());
+            }
+        }
+
+        voter.poll_id = poll_id;
+        voter.cid = ranking[0];
+        voter.has_voted = true;
+        voter.rent_payer = ctx.accounts.user.key();
+        voter.weight = allowed_weight;
+        voter.ballot = ranking;
+
+        poll.voters += 1;
+
+        Ok(())
+    }
+
+    // Approved candidates are passed through remaining accounts in the same
+    // order as `cids`.
+    pub fn vote_approval<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VoteApproval<'info>>,
+        poll_id: u64,
+        cids: Vec<u64>,
+        eligibility: Option<EligibilityProof>,
+    ) -> Result<()> {
+        let voter = &mut ctx.accounts.voter;
+        let poll = &mut ctx.accounts.poll;
+
+        let max_approvals = match poll.mode {
+            PollMode::Approval { max_approvals } => max_approvals,
+            _ => return Err(errors::VoteError::WrongPollMode.into()),
+        };
+
+        if voter.has_voted {
+            return Err(errors::VoteError::VoterAlreadyVoted.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_open(current_timestamp) {
+            return Err(errors::VoteError::PollNotActive.into());
+        }
+
+        let allowed_weight = check_eligibility(
+            poll,
+            &ctx.accounts.user.key(),
+            &eligibility,
+            ctx.accounts.audience_record.as_ref(),
+        )?;
+
+        if cids.is_empty()
+            || cids.len() > max_approvals as usize
+            || cids.len() != ctx.remaining_accounts.len()
+        {
+            return Err(errors::VoteError::InvalidBallot.into());
+        }
+
+        let mut tally = ctx.accounts.tally.load_mut()?;
+        for (i, (cid, account_info)) in cids.iter().zip(ctx.remaining_accounts.iter()).enumerate() {
+            if cids[..i].contains(cid) {
+                return Err(errors::VoteError::InvalidBallot.into());
+            }
+            if !account_info.is_writable {
+                return Err(errors::VoteError::InvalidCandidateAccount.into());
+            }
+
+            let mut candidate = Account::<Candidate>::try_from(account_info)?;
+            if !candidate.has_registered || candidate.poll_id != poll_id || candidate.cid != *cid {
+                return Err(errors::VoteError::InvalidCandidateAccount.into());
+            }
+
+            candidate.votes += allowed_weight;
+            candidate.exit(ctx.program_id)?;
+            tally.add(*cid, allowed_weight);
+        }
+
+        voter.poll_id = poll_id;
+        voter.cid = cids[0];
+        voter.has_voted = true;
+        voter.rent_payer = ctx.accounts.user.key();
+        voter.weight = allowed_weight;
+        voter.ballot = cids;
+
+        poll.voters += 1;
+
+        Ok(())
+    }
+
+    // Sets the voter's votes for one candidate; can be called again to change
+    // the allocation until the poll ends.
+    pub fn vote_quadratic(
+        ctx: Context<VoteQuadratic>,
+        poll_id: u64,
+        cid: u64,
+        votes: u64,
+        eligibility: Option<EligibilityProof>,
+    ) -> Result<()> {
+        let voter = &mut ctx.accounts.voter;
+        let candidate = &mut ctx.accounts.candidate;
+        let poll = &mut ctx.accounts.poll;
+
+        let credits = match poll.mode {
+            PollMode::Quadratic { credits } => credits,
+            _ => return Err(errors::VoteError::WrongPollMode.into()),
+        };
+
+        if !candidate.has_registered || candidate.poll_id != poll_id {
+            return Err(errors::VoteError::CandidateNotRegistered.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_open(current_timestamp) {
+            return Err(errors::VoteError::PollNotActive.into());
+        }
+
+        // eligibility is checked on the first allocation only
+        if !voter.has_voted {
+            check_eligibility(
+                poll,
+                &ctx.accounts.user.key(),
+                &eligibility,
+                ctx.accounts.audience_record.as_ref(),
+            )?;
+
+            voter.poll_id = poll_id;
+            voter.cid = cid;
+            voter.has_voted = true;
+            voter.rent_payer = ctx.accounts.user.key();
+            voter.weight = 1;
+            poll.voters += 1;
+        }
+
+        let previous = voter.allocate_quadratic(cid, votes, credits)?;
+
+        candidate.votes = candidate.votes - previous + votes;
+
+        let mut tally = ctx.accounts.tally.load_mut()?;
+        tally.sub(cid, previous);
+        tally.add(cid, votes);
+
+        Ok(())
+    }
+
+    pub fn add_survey_question(
+        ctx: Context<AddSurveyQuestion>,
+        poll_id: u64,
+        prompt: String,
+        options: Vec<String>,
+        multi_choice: bool,
+    ) -> Result<()> {
+        let poll = &ctx.accounts.poll;
+        let survey = &mut ctx.accounts.survey;
+
+        if poll.mode != PollMode::Survey {
+            return Err(errors::VoteError::WrongPollMode.into());
+        }
+
+        if poll.creator != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotPollCreator.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp >= poll.start {
+            return Err(errors::VoteError::PollAlreadyStarted.into());
+        }
+
+        if survey.questions.len() >= MAX_SURVEY_QUESTIONS {
+            return Err(errors::VoteError::TooManyQuestions.into());
+        }
+
+        if prompt.trim().is_empty()
+            || prompt.len() > 128
+            || options.len() < 2
+            || options.len() > MAX_SURVEY_OPTIONS
+            || options.iter().any(|o| o.trim().is_empty() || o.len() > 32)
+        {
+            return Err(errors::VoteError::InvalidQuestion.into());
+        }
+
+        survey.poll_id = poll_id;
+        survey.rent_payer = ctx.accounts.user.key();
+        let tallies = vec![0; options.len()];
+        survey.questions.push(SurveyQuestion {
+            prompt,
+            options,
+            multi_choice,
+            tallies,
+        });
+
+        Ok(())
+    }
+
+    // `answers[i]` is a bitmask of the options picked for question i; single
+    // choice questions need exactly one bit set, multi choice at least one.
+    pub fn vote_survey(
+        ctx: Context<VoteSurvey>,
+        poll_id: u64,
+        answers: Vec<u8>,
+        eligibility: Option<EligibilityProof>,
+    ) -> Result<()> {
+        let voter = &mut ctx.accounts.voter;
+        let survey = &mut ctx.accounts.survey;
+        let poll = &mut ctx.accounts.poll;
+
+        if poll.mode != PollMode::Survey {
+            return Err(errors::VoteError::WrongPollMode.into());
+        }
+
+        if voter.has_voted {
+            return Err(errors::VoteError::VoterAlreadyVoted.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_open(current_timestamp) {
+            return Err(errors::VoteError::PollNotActive.into());
+        }
+
+        let allowed_weight = check_eligibility(
+            poll,
+            &ctx.accounts.user.key(),
+            &eligibility,
+            ctx.accounts.audience_record.as_ref(),
+        )?;
+
+        if answers.len() != survey.questions.len() {
+            return Err(errors::VoteError::InvalidAnswers.into());
+        }
+
+        for (question, mask) in survey.questions.iter_mut().zip(answers.iter()) {
+            let picked = mask.count_ones();
+            let in_range = (*mask as u16) < (1u16 << question.options.len());
+            let valid = if question.multi_choice { picked >= 1 } else { picked == 1 };
+            if !in_range || !valid {
+                return Err(errors::VoteError::InvalidAnswers.into());
+            }
+
+            for (i, tally) in question.tallies.iter_mut().enumerate() {
+                if mask & (1 << i) != 0 {
+                    *tally += allowed_weight;
+                }
+            }
+        }
+
+        voter.poll_id = poll_id;
+        voter.has_voted = true;
+        voter.rent_payer = ctx.accounts.user.key();
+        voter.weight = allowed_weight;
+
+        poll.voters += 1;
+
+        Ok(())
+    }
+
+    // The commitment is sha256(poll_id le bytes || voter pubkey || cid le bytes
+    // || salt), so it cannot be copied to another wallet or poll.
+    pub fn commit_vote(
+        ctx: Context<CommitVote>,
+        poll_id: u64,
+        commitment: [u8; 32],
+        eligibility: Option<EligibilityProof>,
+    ) -> Result<()> {
+        let voter = &mut ctx.accounts.voter;
+        let poll = &mut ctx.accounts.poll;
+
+        if !matches!(poll.mode, PollMode::CommitReveal { .. }) {
+            return Err(errors::VoteError::WrongPollMode.into());
+        }
+
+        if voter.has_voted {
+            return Err(errors::VoteError::VoterAlreadyVoted.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_open(current_timestamp) {
+            return Err(errors::VoteError::PollNotActive.into());
+        }
+
+        let allowed_weight = check_eligibility(
+            poll,
+            &ctx.accounts.user.key(),
+            &eligibility,
+            ctx.accounts.audience_record.as_ref(),
+        )?;
+
+        voter.poll_id = poll_id;
+        voter.has_voted = true;
+        voter.rent_payer = ctx.accounts.user.key();
+        voter.weight = allowed_weight;
+        voter.commitment = commitment;
+        voter.revealed = false;
+
+        poll.voters += 1;
+
+        Ok(())
+    }
+
+    pub fn reveal_vote(ctx: Context<RevealVote>, poll_id: u64, cid: u64, salt: [u8; 32]) -> Result<()> {
+        let voter = &mut ctx.accounts.voter;
+        let candidate = &mut ctx.accounts.candidate;
+        let poll = &mut ctx.accounts.poll;
+
+        let reveal_end = match poll.mode {
+            PollMode::CommitReveal { reveal_end } => reveal_end,
+            _ => return Err(errors::VoteError::WrongPollMode.into()),
+        };
+
+        if poll.cancelled {
+            return Err(errors::VoteError::PollCancelled.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp <= poll.end || current_timestamp > reveal_end {
+            return Err(errors::VoteError::RevealNotOpen.into());
+        }
+
+        if !candidate.has_registered || candidate.poll_id != poll_id {
+            return Err(errors::VoteError::CandidateNotRegistered.into());
+        }
+
+        if voter.revealed {
+            return Err(errors::VoteError::AlreadyRevealed.into());
+        }
+
+        let user = ctx.accounts.user.key();
+        let expected = hashv(&[
+            &poll_id.to_le_bytes(),
+            user.as_ref(),
+            &cid.to_le_bytes(),
+            &salt,
+        ]);
+        if expected.to_bytes() != voter.commitment {
+            return Err(errors::VoteError::CommitmentMismatch.into());
+        }
+
+        voter.cid = cid;
+        voter.revealed = true;
+
+        candidate.votes += voter.weight;
+        ctx.accounts.tally.load_mut()?.add(cid, voter.weight);
+        poll.revealed += 1;
+
+        Ok(())
+    }
+
+    // Permissionless once voting (and any reveal window) is over. Results are
+    // read from the poll's tally; ranked-choice polls take their winner from the
+    // finished instant-runoff results instead. Surveys have no winner to record.
+    pub fn finalize_poll(ctx: Context<FinalizePoll>, _poll_id: u64) -> Result<()> {
+        let poll = &mut ctx.accounts.poll;
+
+        if poll.finalized {
+            return Err(errors::VoteError::PollAlreadyFinalized.into());
+        }
+
+        if poll.cancelled {
+            return Err(errors::VoteError::PollCancelled.into());
+        }
+
+        if poll.mode == PollMode::Survey {
+            return Err(errors::VoteError::WrongPollMode.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp <= poll.voting_closes_at() {
+            return Err(errors::VoteError::PollNotEnded.into());
+        }
+
+        let mut total_votes: u64 = 0;
+        let mut top_votes: u64 = 0;
+        let mut winners: Vec<u64> = Vec::new();
+        let mut tie = false;
+
+        if poll.mode == PollMode::RankedChoice {
+            let results = match ctx.accounts.ranked_results.as_ref() {
+                Some(results) if results.finished => results,
+                _ => return Err(errors::VoteError::TallyNotFinished.into()),
+            };
+            if let Some(first_round) = results.rounds.first() {
+                total_votes = first_round.tallies.iter().sum::<u64>() + first_round.exhausted;
+            }
+            if results.winner != 0 {
+                winners.push(results.winner);
+                if let Some(last_round) = results.rounds.last() {
+                    top_votes = last_round.tallies[(results.winner - 1) as usize];
+                }
+            }
+        } else {
+            (total_votes, top_votes, winners) = ctx.accounts.tally.load()?.leaders(poll.candidates);
+            tie = winners.len() > 1;
+        }
+
+        poll.outcome = poll.judge(total_votes, top_votes, winners.len());
+        poll.total_votes = total_votes;
+        poll.winners = winners;
+        poll.tie = tie;
+        poll.unrevealed = match poll.mode {
+            PollMode::CommitReveal { .. } => poll.voters - poll.revealed,
+            _ => 0,
+        };
+        poll.finalized = true;
+        poll.finalized_at = current_timestamp;
+
+        Ok(())
+    }
+
+    // Voter accounts can be closed once the poll is cancelled, or finalized and
+    // past the grace period, or at any time after the poll account is closed.
+    pub fn close_voter(ctx: Context<CloseVoter>, _poll_id: u64) -> Result<()> {
+        check_poll_closable(&ctx.accounts.poll)
+    }
+
+    // Ranked-choice results, survey results and NFT vote receipts follow the
+    // same rules as voter accounts; rent goes back to whoever paid for them.
+    pub fn close_ranked_results(ctx: Context<CloseRankedResults>, _poll_id: u64) -> Result<()> {
+        check_poll_closable(&ctx.accounts.poll)
+    }
+
+    pub fn close_survey(ctx: Context<CloseSurvey>, _poll_id: u64) -> Result<()> {
+        check_poll_closable(&ctx.accounts.poll)
+    }
+
+    pub fn close_nft_receipt(
+        ctx: Context<CloseNftReceipt>,
+        _poll_id: u64,
+        _mint: Pubkey,
+    ) -> Result<()> {
+        check_poll_closable(&ctx.accounts.poll)
+    }
+
+    pub fn close_candidate(ctx: Context<CloseCandidate>, _poll_id: u64, _cid: u64) -> Result<()> {
+        let poll = &mut ctx.accounts.poll;
+
+        if poll.creator != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotPollCreator.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_closable(current_timestamp) {
+            return Err(errors::VoteError::PollNotClosable.into());
+        }
+
+        if ctx.accounts.candidate.deposit > 0 {
+            return Err(errors::VoteError::DepositNotSettled.into());
+        }
+
+        poll.closed_candidates += 1;
+
+        Ok(())
+    }
+
+    // closes the poll after all its candidates; the results survive in the `PollClosed` event
+    pub fn close_poll(ctx: Context<ClosePoll>, _poll_id: u64) -> Result<()> {
+        let poll = &ctx.accounts.poll;
+
+        if poll.creator != ctx.accounts.user.key() {
+            return Err(errors::VoteError::NotPollCreator.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_closable(current_timestamp) {
+            return Err(errors::VoteError::PollNotClosable.into());
+        }
+
+        if poll.closed_candidates < poll.candidates {
+            return Err(errors::VoteError::CandidatesStillOpen.into());
+        }
+
+        emit!(PollClosed {
+            poll_id: poll.id,
+            creator: poll.creator,
+            candidates: poll.candidates,
+            voters: poll.voters,
+            total_votes: poll.total_votes,
+            winners: poll.winners.clone(),
+            tie: poll.tie,
+            unrevealed: poll.unrevealed,
+            outcome: poll.outcome,
+        });
+
+        Ok(())
+    }
+
+    // Instant-runoff count. Every round needs each voter account of the poll
+    // passed once through remaining accounts; large polls can split a round
+    // over as many transactions as needed.
+    pub fn tally_ranked<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TallyRanked<'info>>,
+        poll_id: u64,
+    ) -> Result<()> {
+        let poll = &ctx.accounts.poll;
+        let results = &mut ctx.accounts.results;
+
+        if poll.mode != PollMode::RankedChoice {
+            return Err(errors::VoteError::WrongPollMode.into());
+        }
+
+        if poll.cancelled {
+            return Err(errors::VoteError::PollCancelled.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if current_timestamp <= poll.end {
+            return Err(errors::VoteError::PollNotEnded.into());
+        }
+
+        if results.finished {
+            return Err(errors::VoteError::TallyFinished.into());
+        }
+
+        let candidates = poll.candidates as usize;
+        if results.round == 0 {
+            results.poll_id = poll_id;
+            results.rent_payer = ctx.accounts.payer.key();
+            results.round = 1;
+            results.tallies = vec![0; candidates];
+            results.eliminated = vec![false; candidates];
+        }
+
+        for account_info in ctx.remaining_accounts.iter() {
+            if !account_info.is_writable {
+                return Err(errors::VoteError::InvalidVoterAccount.into());
+            }
+            let mut voter = Account::<Voter>::try_from(account_info)?;
+            if voter.poll_id != poll_id || !voter.has_voted {
+                return Err(errors::VoteError::InvalidVoterAccount.into());
+            }
+            if voter.tallied_round >= results.round {
+                continue;
+            }
+
+            let preference = voter
+                .ballot
+                .iter()
+                .find(|cid| !results.eliminated[(**cid - 1) as usize]);
+            match preference {
+                Some(cid) => results.tallies[(*cid - 1) as usize] += voter.weight,
+                None => results.exhausted += voter.weight,
+            }
+
+            voter.tallied_round = results.round;
+            voter.exit(ctx.program_id)?;
+            results.processed += 1;
+        }
+
+        if results.processed < poll.voters {
+            return Ok(());
+        }
+
+        // round complete: look for a majority, otherwise drop the weakest candidate
+        let continuing: u64 = results.tallies.iter().sum();
+        let remaining: Vec<usize> = (0..candidates).filter(|i| !results.eliminated[*i]).collect();
+
+        let mut leader: Option<usize> = None;
+        let mut trailer: Option<usize> = None;
+        for i in remaining.iter().copied() {
+            let leads = match leader {
+                Some(l) => results.tallies[i] > results.tallies[l],
+                None => true,
+            };
+            if leads {
+                leader = Some(i);
+            }
+            // ties for last place eliminate the later-registered candidate
+            let trails = match trailer {
+                Some(t) => results.tallies[i] <= results.tallies[t],
+                None => true,
+            };
+            if trails {
+                trailer = Some(i);
+            }
+        }
+
+        let decided = match leader {
+            None => true,
+            Some(_) if continuing == 0 => true,
+            Some(l) => results.tallies[l] * 2 > continuing || remaining.len() == 1,
+        };
+
+        let eliminated = if decided {
+            results.winner = match leader {
+                Some(l) if continuing > 0 => l as u64 + 1,
+                _ => 0,
+            };
+            results.finished = true;
+            0
+        } else {
+            let t = trailer.unwrap();
+            results.eliminated[t] = true;
+            t as u64 + 1
+        };
+
+        let round = RankedRound {
+            tallies: results.tallies.clone(),
+            exhausted: results.exhausted,
+            eliminated,
+        };
+        results.rounds.push(round);
+
+        if !results.finished {
+            results.round += 1;
+            results.processed = 0;
+            results.exhausted = 0;
+            results.tallies = vec![0; candidates];
+        }
+
+        Ok(())
+    }
+
+    // move a single-choice vote to another candidate while the poll is open
+    pub fn change_vote(ctx: Context<ChangeVote>, poll_id: u64, new_cid: u64) -> Result<()> {
+        let voter = &mut ctx.accounts.voter;
+        let poll = &ctx.accounts.poll;
+        let current_candidate = &mut ctx.accounts.current_candidate;
+        let new_candidate = &mut ctx.accounts.new_candidate;
+
+        if !matches!(poll.mode, PollMode::Standard | PollMode::TokenWeighted { .. }) {
+            return Err(errors::VoteError::WrongPollMode.into());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_open(current_timestamp) {
+            return Err(errors::VoteError::PollNotActive.into());
+        }
+
+        if voter.delegate.is_some() {
+            return Err(errors::VoteError::DelegatedVote.into());
+        }
+
+        if new_cid == voter.cid {
+            return Err(errors::VoteError::SameCandidate.into());
+        }
+
+        if !new_candidate.has_registered || new_candidate.poll_id != poll_id {
+            return Err(errors::VoteError::CandidateNotRegistered.into());
+        }
+
+        current_candidate.votes -= voter.weight;
+        new_candidate.votes += voter.weight;
+
+        let mut tally = ctx.accounts.tally.load_mut()?;
+        tally.sub(voter.cid, voter.weight);
+        tally.add(new_cid, voter.weight);
+
+        voter.cid = new_cid;
+
+        Ok(())
+    }
+
+    // withdraw a single-choice vote; the voter account is closed and its rent refunded
+    pub fn revoke_vote(ctx: Context<RevokeVote>, _poll_id: u64) -> Result<()> {
+        let voter = &ctx.accounts.voter;
+        let poll = &mut ctx.accounts.poll;
+        let candidate = &mut ctx.accounts.candidate;
+
+        match poll.mode {
+            PollMode::Standard | PollMode::TokenWeighted { escrow: false, .. } => {}
+            PollMode::TokenWeighted { escrow: true, .. } => {
+                return Err(errors::VoteError::VoteLocked.into());
+            }
+            _ => return Err(errors::VoteError::WrongPollMode.into()),
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        if !poll.is_open(current_timestamp) {
+            return Err(errors::VoteError::PollNotActive.into());
+        }
+
+        if voter.delegate.is_some() {
+            return Err(errors::VoteError::DelegatedVote.into());
+        }
+        if voter.delegated_weight > 0 {
+            return Err(errors::VoteError::VoteHasDelegations.into());
+        }
+
+        candidate.votes -= voter.weight;
+        ctx.accounts.tally.load_mut()?.sub(voter.cid, voter.weight);
+        poll.voters -= 1;
+
+        Ok(())
+    }
+
+    // return escrowed voting tokens once the poll is over, or at any time
+    // after the poll account is closed
+    pub fn release_vote_escrow(ctx: Context<ReleaseVoteEscrow>, poll_id: u64) -> Result<()> {
+        if !ctx.accounts.poll.data_is_empty() {
+            let data = ctx.accounts.poll.try_borrow_data()?;
+            let poll = Poll::try_deserialize(&mut &data[..])?;
+            let current_timestamp = Clock::get()?.unix_timestamp as u64;
+            if !poll.cancelled && current_timestamp <= poll.end {
+                return Err(errors::VoteError::PollNotEnded.into());
+            }
+        }
+
+        let user_key = ctx.accounts.user.key();
+        let poll_id_bytes = poll_id.to_le_bytes();
+        let bump = [ctx.bumps.vote_escrow];
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vote_escrow",
+            poll_id_bytes.as_ref(),
+            user_key.as_ref(),
+            &bump,
+        ]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vote_escrow.to_account_info(),
+                    to: ctx.accounts.voter_token_account.to_account_info(),
+                    authority: ctx.accounts.vote_escrow.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            ctx.accounts.vote_escrow.amount,
+        )?;
+
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.vote_escrow.to_account_info(),
+                destination: ctx.accounts.user.to_account_info(),
+                authority: ctx.accounts.vote_escrow.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        Ok(())
+    }
+
+    // Conviction voting: the platform treasury funds proposals once enough
+    // stake has sat on them for long enough, with no poll window involved.
+    pub fn initialize_treasury(
+        ctx: Context<InitializeTreasury>,
+        decay: u64,
+        max_ratio_bps: u16,
+        weight: u64,
+        period: u64,
+        fee_share_bps: u16,
+    ) -> Result<()> {
+        if ctx.accounts.authority.key() != ctx.accounts.program_state.platform_address {
+            msg!("Unauthorized treasury initialization");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if decay >= CONVICTION_SCALE
+            || max_ratio_bps == 0
+            || max_ratio_bps > 10_000
+            || weight == 0
+            || period == 0
+            || fee_share_bps > 10_000
+        {
+            return Err(errors::VoteError::InvalidConvictionParams.into());
+        }
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.authority = ctx.accounts.authority.key();
+        treasury.stake_mint = ctx.accounts.stake_mint.key();
+        treasury.decay = decay;
+        treasury.max_ratio_bps = max_ratio_bps;
+        treasury.weight = weight;
+        treasury.period = period;
+        treasury.total_staked = 0;
+        treasury.proposals = 0;
+        treasury.fee_share_bps = fee_share_bps;
+
+        Ok(())
+    }
+
+    // direct deposits, on top of the fee share routed in by `withdraw`
+    pub fn fund_treasury(ctx: Context<FundTreasury>, amount: u64) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    pub fn create_funding_proposal(
+        ctx: Context<CreateFundingProposal>,
+        proposal_id: u64,
+        description: String,
+        requested: u64,
+        beneficiary: Pubkey,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        if treasury.proposals + 1 != proposal_id {
+            return Err(errors::VoteError::PollCounterUnderflow.into());
+        }
+
+        if requested == 0 {
+            return Err(errors::VoteError::InvalidConvictionParams.into());
+        }
+
+        if description.len() > 280 {
+            return Err(errors::VoteError::DescriptionTooLong.into());
+        }
+        treasury.proposals += 1;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.id = proposal_id;
+        proposal.proposer = ctx.accounts.user.key();
+        proposal.beneficiary = beneficiary;
+        proposal.description = description;
+        proposal.requested = requested;
+        proposal.staked = 0;
+        proposal.conviction = 0;
+        proposal.updated_at = Clock::get()?.unix_timestamp as u64;
+        proposal.executed = false;
+
+        Ok(())
+    }
+
+    pub fn stake_conviction(ctx: Context<StakeConviction>, proposal_id: u64, amount: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let proposal = &mut ctx.accounts.proposal;
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        proposal.stake(treasury, amount, current_timestamp)?;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.staker_token_account.to_account_info(),
+                    to: ctx.accounts.stake_escrow.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let stake = &mut ctx.accounts.stake;
+        stake.proposal_id = proposal_id;
+        stake.staker = ctx.accounts.user.key();
+        stake.amount += amount;
+
+        Ok(())
+    }
+
+    // withdraw staked tokens; allowed at any time, also after execution
+    pub fn unstake_conviction(ctx: Context<UnstakeConviction>, proposal_id: u64, amount: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let proposal = &mut ctx.accounts.proposal;
+        let stake = &mut ctx.accounts.stake;
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        proposal.unstake(treasury, stake.amount, amount, current_timestamp)?;
+
+        let user_key = ctx.accounts.user.key();
+        let proposal_id_bytes = proposal_id.to_le_bytes();
+        let bump = [ctx.bumps.stake_escrow];
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"conviction_escrow",
+            proposal_id_bytes.as_ref(),
+            user_key.as_ref(),
+            &bump,
+        ]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.stake_escrow.to_account_info(),
+                    to: ctx.accounts.staker_token_account.to_account_info(),
+                    authority: ctx.accounts.stake_escrow.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        stake.amount -= amount;
+
+        Ok(())
+    }
+
+    // pays a proposal out of the treasury once its conviction crosses the threshold
+    pub fn execute_funding_proposal(ctx: Context<ExecuteFundingProposal>, _proposal_id: u64) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let proposal = &mut ctx.accounts.proposal;
+
+        let treasury_info = treasury.to_account_info();
+        let rent_balance = Rent::get()?.minimum_balance(treasury_info.data_len());
+        let funds = treasury_info.lamports().saturating_sub(rent_balance);
+
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        proposal.execute(treasury, funds, current_timestamp)?;
+
+        **treasury_info.try_borrow_mut_lamports()? -= proposal.requested;
+        **ctx.accounts.beneficiary.try_borrow_mut_lamports()? += proposal.requested;
+
+        Ok(())
+    }
+
+
+
+
+    // ==============================
+    // Crowdfunding
+    // ==============================
+
+    // program setup
+    pub fn initialize(ctx: Context<InitializeCtx>) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let deployer = &ctx.accounts.deployer;
+
+        if state.initialized {
+            msg!("Program already initialized");
+            return Err(ErrorCode::AlreadyInitialized.into());
+        }
+
+        state.campaign_count = 0;
+        state.platform_fee = 5;
+        state.platform_address = deployer.key();
+        state.initialized = true;
+
+        msg!("Program initialized successfully");
+        Ok(())
+    }
+
+    // create a campaign
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_campaign(
+        ctx: Context<CreateCampaignCtx>,
+        title: String,
+        description: String,
+        image_url: String,
+        goal: u64,
+        deadline: u64,
+        funding_model: FundingModel,
+        milestones: Vec<MilestoneSpec>,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let state = &mut ctx.accounts.program_state;
+        let now = Clock::get()?.unix_timestamp as u64;
+
+        if title.len() > 64 {
+            msg!("Title length invalid");
+            return Err(ErrorCode::TitleTooLong.into());
+        }
+        if description.len() > 512 {
+            msg!("Description length invalid");
+            return Err(ErrorCode::DescriptionTooLong.into());
+        }
+        if image_url.len() > 256 {
+            msg!("Image URL length invalid");
+            return Err(ErrorCode::ImageUrlTooLong.into());
+        }
+        // token goals are in the mint's base units
+        let mint = ctx.accounts.mint.as_ref().map(|mint| mint.key());
+        if goal == 0 || (mint.is_none() && goal < 1_000_000_000) {
+            msg!("Campaign goal too small");
+            return Err(ErrorCode::InvalidGoalAmount.into());
+        }
+
+        if mint.is_some() && ctx.accounts.vault.is_none() {
+            msg!("Token campaigns need a vault");
+            return Err(ErrorCode::WrongCurrency.into());
+        }
+
+        if (deadline != 0 && deadline <= now)
+            || (deadline == 0 && funding_model == FundingModel::AllOrNothing)
+        {
+            msg!("Campaign deadline must be in the future");
+            return Err(ErrorCode::InvalidDeadline.into());
+        }
+
+        if !milestones.is_empty()
+            && (milestones.len() > MAX_MILESTONES
+                || milestones.iter().any(|m| m.amount == 0 || m.description.len() > 128)
+                || milestones.iter().map(|m| m.amount).sum::<u64>() != goal)
+        {
+            msg!("Milestone amounts must be positive and add up to the goal");
+            return Err(ErrorCode::InvalidMilestones.into());
+        }
+
+        if state.crowdfunding_paused {
+            msg!("Crowdfunding is paused");
+            return Err(ErrorCode::ModulePaused.into());
+        }
+
+        state.campaign_count += 1;
+
+        campaign.cid = state.campaign_count;
+        campaign.creator = ctx.accounts.creator.key();
+        campaign.title = title;
+        campaign.description = description;
+        campaign.image_url = image_url;
+        campaign.goal = goal;
+        campaign.amount_raised = 0;
+        campaign.donors = 0;
+        campaign.withdrawals = 0;
+        campaign.timestamp = now;
+        campaign.active = true;
+        campaign.balance = 0;
+        campaign.deadline = deadline;
+        campaign.funding_model = funding_model;
+        campaign.mint = mint;
+        campaign.milestones = milestones
+            .into_iter()
+            .enumerate()
+            .map(|(i, m)| Milestone {
+                amount: m.amount,
+                description: m.description,
+                status: if i == 0 { MilestoneStatus::Approved } else { MilestoneStatus::Locked },
+                voting_start: 0,
+                voting_end: 0,
+                eligible_weight: 0,
+                approve_weight: 0,
+                reject_weight: 0,
+            })
+            .collect();
+
+        msg!("New campaign successfully created");
+        Ok(())
+    }
+
+    // edit campaign details
+    pub fn update_campaign(
+        ctx: Context<UpdateCampaignCtx>,
+        cid: u64,
+        title: String,
+        description: String,
+        image_url: String,
+        goal: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &mut ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized attempt to update");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign ID mismatch");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if title.len() > 64 {
+            msg!("Title exceeds limit");
+            return Err(ErrorCode::TitleTooLong.into());
+        }
+        if description.len() > 512 {
+            msg!("Description exceeds limit");
+            return Err(ErrorCode::DescriptionTooLong.into());
+        }
+        if image_url.len() > 256 {
+            msg!("Image URL exceeds limit");
+            return Err(ErrorCode::ImageUrlTooLong.into());
+        }
+        if goal == 0 || (campaign.mint.is_none() && goal < 1_000_000_000) {
+            msg!("Goal is too small");
+            return Err(ErrorCode::InvalidGoalAmount.into());
+        }
+
+        if campaign.funding_model == FundingModel::AllOrNothing
+            && campaign.amount_raised > 0
+            && goal != campaign.goal
+        {
+            msg!("Goal is locked once an all-or-nothing campaign has donations");
+            return Err(ErrorCode::InvalidGoalAmount.into());
+        }
+
+        if !campaign.milestones.is_empty() && goal != campaign.goal {
+            msg!("Goal is fixed by the campaign's milestones");
+            return Err(ErrorCode::InvalidGoalAmount.into());
+        }
+
+        campaign.title = title;
+        campaign.description = description;
+        campaign.image_url = image_url;
+        campaign.goal = goal;
+
+        msg!("Campaign updated successfully");
+        Ok(())
+    }
+
+    // deactivate campaign
+    pub fn delete_campaign(ctx: Context<DeleteCampaignCtx>, cid: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &mut ctx.accounts.creator;
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized attempt to deactivate");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign not found");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.active {
+            msg!("Campaign already inactive");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        campaign.active = false;
+
+        msg!("Campaign successfully deactivated");
+        Ok(())
+    }
+
+    // donate to campaign
+    pub fn donate(ctx: Context<DonateCtx>, cid: u64, amount: u64, tier: u8) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let donor = &mut ctx.accounts.donor;
+        let transaction = &mut ctx.accounts.transaction;
+
+        if ctx.accounts.program_state.crowdfunding_paused {
+            msg!("Crowdfunding is paused");
+            return Err(ErrorCode::ModulePaused.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign does not exist for donation");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.mint.is_some() {
+            msg!("Token campaign, use donate_token");
+            return Err(ErrorCode::WrongCurrency.into());
+        }
+
+        if !campaign.active {
+            msg!("Inactive campaign — donation rejected");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        if amount < 1_000_000 {
+            msg!("Donation is less than 0.0001 SOL");
+            return Err(ErrorCode::InvalidDonationAmount.into());
+        }
+
+        if campaign.amount_raised >= campaign.goal {
+            msg!("Target already achieved");
+            return Err(ErrorCode::CampaignGoalActualized.into());
+        }
+
+        if campaign.is_past_deadline(Clock::get()?.unix_timestamp as u64) {
+            msg!("Campaign deadline has passed");
+            return Err(ErrorCode::CampaignEnded.into());
+        }
+
+        campaign.reserve_tier(tier, amount)?;
+
+        let tx_instruction = system_instruction::transfer(
+            &donor.key(),
+            &campaign.key(),
+            amount,
+        );
+
+        let result = invoke(
+            &tx_instruction,
+            &[donor.to_account_info(), campaign.to_account_info()],
+        );
+
+        if let Err(_e) = result {
+            msg!("Failed to transfer donation");
+            return Err(ErrorCode::InsufficientFund.into());
+        }
+
+        campaign.amount_raised += amount;
+        campaign.balance += amount;
+        campaign.donors += 1;
+        if donor.key() == campaign.creator {
+            campaign.creator_donated += amount;
+        }
+
+        transaction.amount = amount;
+        transaction.cid = cid;
+        transaction.owner = donor.key();
+        transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+        transaction.credited = true;
+        transaction.tier = tier;
+
+        msg!("Donation processed successfully");
+        Ok(())
+    }
+
+     // withdraw funds from a campaign
+    pub fn withdraw(ctx: Context<WithdrawCtx>, cid: u64, amount: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+        let transaction = &mut ctx.accounts.transaction;
+        let state = &mut ctx.accounts.program_state;
+        let platform_account_info = &ctx.accounts.platform_address;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for withdrawal");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized withdrawal attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.mint.is_some() {
+            msg!("Token campaign, use withdraw_token");
+            return Err(ErrorCode::WrongCurrency.into());
+        }
+
+        //fixing the amount such that  less than 1 sol cant be deducted
+        if amount < 1_000_000_000 {
+            msg!("Withdrawal amount too low");
+            return Err(ErrorCode::InvalidWithdrawalAmount.into());
+        }
+
+        if amount > campaign.balance {
+            msg!("Withdrawal exceeds campaign balance");
+            return Err(ErrorCode::CampaignGoalActualized.into());
+        }
+
+        if campaign.funding_model == FundingModel::AllOrNothing
+            && campaign.amount_raised < campaign.goal
+        {
+            msg!("All-or-nothing campaign has not reached its goal");
+            return Err(ErrorCode::GoalNotReached.into());
+        }
+
+        if campaign.withdrawn + amount > campaign.unlocked() {
+            msg!("Withdrawal exceeds the funds unlocked by milestones");
+            return Err(ErrorCode::MilestoneLocked.into());
+        }
+
+        if platform_account_info.key() != state.platform_address {
+            msg!("Invalid platform address for withdrawal");
+            return Err(ErrorCode::InvalidPlatformAddress.into());
+        }
+
+        let rent_balance = Rent::get()?.minimum_balance(campaign.to_account_info().data_len());
+        if amount > **campaign.to_account_info().lamports.borrow() - rent_balance {
+            msg!("Withdrawal exceeds campaign's usable balance");
+            return Err(ErrorCode::InsufficientFund.into());
+        }
+
+        let platform_fee = amount * state.platform_fee / 100;
+        let creator_amount = amount - platform_fee;
+        let treasury_fee = treasury_fee_share(&ctx.accounts.treasury, ctx.program_id, platform_fee)?;
+
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= creator_amount;
+        **creator.to_account_info().try_borrow_mut_lamports()? += creator_amount;
+
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= platform_fee;
+        **platform_account_info.to_account_info().try_borrow_mut_lamports()? += platform_fee - treasury_fee;
+        **ctx.accounts.treasury.try_borrow_mut_lamports()? += treasury_fee;
+
+        campaign.withdrawals += 1;
+        campaign.balance -= amount;
+        campaign.withdrawn += amount;
+
+        transaction.amount = amount;
+        transaction.cid = cid;
+        transaction.owner = creator.key();
+        transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+        transaction.credited = false;
+
+        msg!("Withdrawal successful");
+        Ok(())
+    }
+
+    // refund a donation to an all-or-nothing campaign that missed its goal,
+    // either past the deadline or after the creator deactivated it
+    pub fn claim_refund(ctx: Context<ClaimRefundCtx>, cid: u64, _donation: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let donor = &ctx.accounts.donor;
+        let transaction = &ctx.accounts.transaction;
+
+        if campaign.cid != cid || transaction.cid != cid {
+            msg!("Campaign not found for refund");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if transaction.owner != donor.key() || !transaction.credited {
+            msg!("Transaction is not a donation from this wallet");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.mint.is_some() {
+            msg!("Token campaign, use claim_token_refund");
+            return Err(ErrorCode::WrongCurrency.into());
+        }
+
+        let ended =
+            !campaign.active || campaign.is_past_deadline(Clock::get()?.unix_timestamp as u64);
+        if campaign.funding_model != FundingModel::AllOrNothing
+            || campaign.amount_raised >= campaign.goal
+            || !ended
+        {
+            msg!("Refunds are not available for this campaign");
+            return Err(ErrorCode::RefundNotAvailable.into());
+        }
+
+        let amount = transaction.amount;
+        **campaign.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **donor.to_account_info().try_borrow_mut_lamports()? += amount;
+        campaign.balance -= amount;
+        campaign.release_tier(transaction.tier);
+
+        msg!("Donation refunded");
+        Ok(())
+    }
+
+    // donate to a campaign denominated in an SPL mint
+    pub fn donate_token(ctx: Context<DonateTokenCtx>, cid: u64, amount: u64, tier: u8) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let donor = &ctx.accounts.donor;
+        let transaction = &mut ctx.accounts.transaction;
+
+        if ctx.accounts.program_state.crowdfunding_paused {
+            msg!("Crowdfunding is paused");
+            return Err(ErrorCode::ModulePaused.into());
+        }
+
+        if campaign.cid != cid {
+            msg!("Campaign does not exist for donation");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if !campaign.active {
+            msg!("Inactive campaign — donation rejected");
+            return Err(ErrorCode::InactiveCampaign.into());
+        }
+
+        if amount == 0 {
+            msg!("Donation amount is zero");
+            return Err(ErrorCode::InvalidDonationAmount.into());
+        }
+
+        if campaign.amount_raised >= campaign.goal {
+            msg!("Target already achieved");
+            return Err(ErrorCode::CampaignGoalActualized.into());
+        }
+
+        if campaign.is_past_deadline(Clock::get()?.unix_timestamp as u64) {
+            msg!("Campaign deadline has passed");
+            return Err(ErrorCode::CampaignEnded.into());
+        }
+
+        campaign.reserve_tier(tier, amount)?;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.donor_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: donor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        campaign.amount_raised += amount;
+        campaign.balance += amount;
+        campaign.donors += 1;
+        if donor.key() == campaign.creator {
+            campaign.creator_donated += amount;
+        }
+
+        transaction.amount = amount;
+        transaction.cid = cid;
+        transaction.owner = donor.key();
+        transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+        transaction.credited = true;
+        transaction.tier = tier;
+
+        msg!("Token donation processed successfully");
+        Ok(())
+    }
+
+    // withdraw from a token campaign; the platform fee is paid in the same mint
+    pub fn withdraw_token(ctx: Context<WithdrawTokenCtx>, cid: u64, amount: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let creator = &ctx.accounts.creator;
+        let transaction = &mut ctx.accounts.transaction;
+        let state = &ctx.accounts.program_state;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found for withdrawal");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.creator != creator.key() {
+            msg!("Unauthorized withdrawal attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if amount == 0 {
+            msg!("Withdrawal amount is zero");
+            return Err(ErrorCode::InvalidWithdrawalAmount.into());
+        }
+
+        if amount > campaign.balance {
+            msg!("Withdrawal exceeds campaign balance");
+            return Err(ErrorCode::InsufficientFund.into());
+        }
+
+        if campaign.funding_model == FundingModel::AllOrNothing
+            && campaign.amount_raised < campaign.goal
+        {
+            msg!("All-or-nothing campaign has not reached its goal");
+            return Err(ErrorCode::GoalNotReached.into());
+        }
+
+        if campaign.withdrawn + amount > campaign.unlocked() {
+            msg!("Withdrawal exceeds the funds unlocked by milestones");
+            return Err(ErrorCode::MilestoneLocked.into());
+        }
+
+        let platform_fee = amount * state.platform_fee / 100;
+        let creator_amount = amount - platform_fee;
+
+        let cid_bytes = cid.to_le_bytes();
+        let bump = [ctx.bumps.vault];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"campaign_vault", cid_bytes.as_ref(), &bump]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.creator_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            creator_amount,
+        )?;
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.platform_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            platform_fee,
+        )?;
+
+        campaign.withdrawals += 1;
+        campaign.balance -= amount;
+        campaign.withdrawn += amount;
+
+        transaction.amount = amount;
+        transaction.cid = cid;
+        transaction.owner = creator.key();
+        transaction.timestamp = Clock::get()?.unix_timestamp as u64;
+        transaction.credited = false;
+
+        msg!("Token withdrawal successful");
+        Ok(())
+    }
+
+    // token counterpart of `claim_refund`
+    pub fn claim_token_refund(ctx: Context<ClaimTokenRefundCtx>, cid: u64, _donation: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let donor = &ctx.accounts.donor;
+        let transaction = &ctx.accounts.transaction;
+
+        if campaign.cid != cid || transaction.cid != cid {
+            msg!("Campaign not found for refund");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if transaction.owner != donor.key() || !transaction.credited {
+            msg!("Transaction is not a donation from this wallet");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        let ended =
+            !campaign.active || campaign.is_past_deadline(Clock::get()?.unix_timestamp as u64);
+        if campaign.funding_model != FundingModel::AllOrNothing
+            || campaign.amount_raised >= campaign.goal
+            || !ended
+        {
+            msg!("Refunds are not available for this campaign");
+            return Err(ErrorCode::RefundNotAvailable.into());
+        }
+
+        let cid_bytes = cid.to_le_bytes();
+        let bump = [ctx.bumps.vault];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"campaign_vault", cid_bytes.as_ref(), &bump]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.donor_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            transaction.amount,
+        )?;
+
+        campaign.balance -= transaction.amount;
+        campaign.release_tier(transaction.tier);
+
+        msg!("Token donation refunded");
+        Ok(())
+    }
+
+    // adds a perk donors can pick with `donate`; its tier id is its position, from 1
+    pub fn add_reward_tier(
+        ctx: Context<AddRewardTierCtx>,
+        cid: u64,
+        min_amount: u64,
+        quantity: u64,
+        description: String,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.creator != ctx.accounts.creator.key() {
+            msg!("Unauthorized reward tier update");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if campaign.tiers.len() >= MAX_REWARD_TIERS
+            || min_amount == 0
+            || quantity == 0
+            || description.len() > 128
+        {
+            msg!("Invalid reward tier");
+            return Err(ErrorCode::InvalidRewardTier.into());
+        }
+
+        campaign.tiers.push(RewardTier {
+            min_amount,
+            quantity,
+            claimed: 0,
+            description,
+        });
+
+        msg!("Reward tier added");
+        Ok(())
+    }
+
+    // opens donor voting on the next locked milestone
+    pub fn request_milestone_approval(
+        ctx: Context<RequestMilestoneApprovalCtx>,
+        cid: u64,
+        index: u8,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if campaign.creator != ctx.accounts.creator.key() {
+            msg!("Unauthorized milestone request");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        let i = index as usize;
+        if i == 0
+            || i >= campaign.milestones.len()
+            || campaign.milestones[i].status != MilestoneStatus::Locked
+            || campaign.milestones[i - 1].status != MilestoneStatus::Approved
+        {
+            msg!("Milestone cannot be put to a vote");
+            return Err(ErrorCode::MilestoneNotOpen.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        let eligible_weight = campaign.amount_raised - campaign.creator_donated;
+        let milestone = &mut campaign.milestones[i];
+        milestone.status = MilestoneStatus::Voting;
+        milestone.voting_start = now;
+        milestone.voting_end = now + MILESTONE_VOTING_PERIOD;
+        milestone.eligible_weight = eligible_weight;
+
+        msg!("Milestone voting opened");
+        Ok(())
+    }
+
+    // Each donation made before voting opened votes once per milestone,
+    // weighted by its amount. The creator's own donations don't count.
+    pub fn vote_milestone(
+        ctx: Context<VoteMilestoneCtx>,
+        cid: u64,
+        index: u8,
+        _donation: u64,
+        approve: bool,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let transaction = &ctx.accounts.transaction;
+
+        if campaign.cid != cid || transaction.cid != cid {
+            msg!("Campaign not found");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if transaction.owner != ctx.accounts.donor.key() || !transaction.credited {
+            msg!("Transaction is not a donation from this wallet");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if transaction.owner == campaign.creator {
+            msg!("Campaign creator cannot vote on milestones");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        let milestone = match campaign.milestones.get_mut(index as usize) {
+            Some(m) if m.status == MilestoneStatus::Voting && now <= m.voting_end => m,
+            _ => {
+                msg!("Milestone is not open for voting");
+                return Err(ErrorCode::MilestoneNotOpen.into());
+            }
+        };
+
+        if transaction.timestamp >= milestone.voting_start {
+            msg!("Donation was made after milestone voting opened");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if approve {
+            milestone.approve_weight += transaction.amount;
+        } else {
+            milestone.reject_weight += transaction.amount;
+        }
+        ctx.accounts.milestone_vote.approve = approve;
+
+        msg!("Milestone vote recorded");
+        Ok(())
+    }
+
+    // Closes a milestone vote. Without a quorum the vote runs for another
+    // period; a rejection deactivates the campaign and sets the locked
+    // remainder aside for `claim_milestone_refund`.
+    pub fn finalize_milestone(ctx: Context<FinalizeMilestoneCtx>, cid: u64, index: u8) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+
+        if campaign.cid != cid {
+            msg!("Campaign not found");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        let milestone = match campaign.milestones.get_mut(index as usize) {
+            Some(m) if m.status == MilestoneStatus::Voting => m,
+            _ => {
+                msg!("Milestone is not being voted on");
+                return Err(ErrorCode::MilestoneNotOpen.into());
+            }
+        };
+
+        if now <= milestone.voting_end {
+            msg!("Milestone voting is still open");
+            return Err(ErrorCode::MilestoneVotingActive.into());
+        }
+
+        let votes = milestone.approve_weight as u128 + milestone.reject_weight as u128;
+        if votes == 0
+            || votes * 10_000 < milestone.eligible_weight as u128 * MILESTONE_QUORUM_BPS as u128
+        {
+            milestone.voting_end = now + MILESTONE_VOTING_PERIOD;
+            msg!("Milestone vote missed quorum, voting extended");
+        } else if milestone.approve_weight > milestone.reject_weight {
+            milestone.status = MilestoneStatus::Approved;
+            msg!("Milestone approved");
+        } else {
+            milestone.status = MilestoneStatus::Rejected;
+            let still_unlocked = campaign.unlocked().saturating_sub(campaign.withdrawn);
+            campaign.refund_pool = campaign.balance.saturating_sub(still_unlocked);
+            campaign.active = false;
+            msg!("Milestone rejected");
+        }
+
+        Ok(())
+    }
+
+    // Refunds a donation's share of the locked remainder after a rejected
+    // milestone. Token campaigns pass their vault and the donor's token account.
+    pub fn claim_milestone_refund(
+        ctx: Context<ClaimMilestoneRefundCtx>,
+        cid: u64,
+        _donation: u64,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        let donor = &ctx.accounts.donor;
+        let transaction = &ctx.accounts.transaction;
+
+        if campaign.cid != cid || transaction.cid != cid {
+            msg!("Campaign not found for refund");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        if transaction.owner != donor.key() || !transaction.credited {
+            msg!("Transaction is not a donation from this wallet");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if !campaign.milestones.iter().any(|m| m.status == MilestoneStatus::Rejected) {
+            msg!("Refunds are not available for this campaign");
+            return Err(ErrorCode::RefundNotAvailable.into());
+        }
+
+        let refund = (transaction.amount as u128 * campaign.refund_pool as u128
+            / campaign.amount_raised as u128) as u64;
+
+        match campaign.mint {
+            None => {
+                **campaign.to_account_info().try_borrow_mut_lamports()? -= refund;
+                **donor.to_account_info().try_borrow_mut_lamports()? += refund;
+            }
+            Some(mint) => {
+                let (vault, donor_token_account, token_program) = match (
+                    ctx.accounts.vault.as_ref(),
+                    ctx.accounts.donor_token_account.as_ref(),
+                    ctx.accounts.token_program.as_ref(),
+                ) {
+                    (Some(vault), Some(donor_token_account), Some(token_program))
+                        if vault.mint == mint =>
+                    {
+                        (vault, donor_token_account, token_program)
+                    }
+                    _ => {
+                        msg!("Token campaign refunds need the vault and a token account");
+                        return Err(ErrorCode::WrongCurrency.into());
+                    }
+                };
+
+                let cid_bytes = cid.to_le_bytes();
+                let bump = [ctx.bumps.vault];
+                let signer_seeds: &[&[&[u8]]] = &[&[b"campaign_vault", cid_bytes.as_ref(), &bump]];
+
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        token_program.to_account_info(),
+                        Transfer {
+                            from: vault.to_account_info(),
+                            to: donor_token_account.to_account_info(),
+                            authority: vault.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    refund,
+                )?;
+            }
+        }
+
+        campaign.balance -= refund;
+        campaign.release_tier(transaction.tier);
+
+        msg!("Milestone refund paid");
+        Ok(())
+    }
+
+    // update platform settings
+    pub fn update_platform_settings(
+        ctx: Context<UpdatePlatformSettingsCtx>,
+        new_platform_fee: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+        let updater = &ctx.accounts.updater;
+
+        if updater.key() != state.platform_address {
+            msg!("Unauthorized update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if !(1..=15).contains(&new_platform_fee) {
+            msg!("Fee percentage not within range");
+            return Err(ErrorCode::InvalidPlatformFee.into());
+        }
+
+        state.platform_fee = new_platform_fee;
+
+        msg!("Platform settings updated");
+        Ok(())
+    }
+
+    // Escrowed polls in `governance_mint` with a quorum of at least
+    // `min_quorum_bps` of its supply can carry platform actions, see
+    // `set_proposal_action`. A zero minimum turns member proposals off.
+    pub fn set_governance_rules(
+        ctx: Context<UpdatePlatformSettingsCtx>,
+        governance_mint: Option<Pubkey>,
+        min_quorum_bps: u16,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.program_state;
+
+        if ctx.accounts.updater.key() != state.platform_address {
+            msg!("Unauthorized update attempt");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        if min_quorum_bps > 10_000 {
+            msg!("Governance quorum above 100%");
+            return Err(ErrorCode::InvalidGovernanceQuorum.into());
+        }
+
+        state.governance_mint = governance_mint;
+        state.governance_min_quorum_bps = min_quorum_bps;
+
+        msg!("Governance rules updated");
+        Ok(())
+    }
+
+    // Grows a `program_state` created before the governance fields were added.
+    // The new fields start zeroed (nothing paused).
+    pub fn migrate_program_state(ctx: Context<MigrateProgramStateCtx>) -> Result<()> {
+        let state_info = ctx.accounts.program_state.to_account_info();
+
+        if state_info.owner != ctx.program_id {
+            msg!("Program state not initialized");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        {
+            // the original layout is a prefix of the current one:
+            // discriminator, initialized, campaign_count, platform_fee, platform_address
+            let data = state_info.try_borrow_data()?;
+            if data.len() < 57 || data[..8] != ProgramState::DISCRIMINATOR {
+                msg!("Account is not the program state");
+                return Err(ErrorCode::Unauthorized.into());
+            }
+            if data[25..57] != ctx.accounts.authority.key().to_bytes() {
+                msg!("Unauthorized migration attempt");
+                return Err(ErrorCode::Unauthorized.into());
+            }
+        }
+
+        realloc_account(
+            &state_info,
+            ANCHOR_DISCRIMINATOR_SIZE + ProgramState::INIT_SPACE,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program,
+        )?;
+
+        msg!("Program state migrated");
+        Ok(())
+    }
+
+    // Grows a campaign created before deadlines, funding models, token
+    // campaigns, milestones and reward tiers to the current layout. The new
+    // fields read as zero: no deadline, keep-what-you-raise, SOL, no schedule.
+    pub fn migrate_campaign(ctx: Context<MigrateCampaignCtx>, _cid: u64) -> Result<()> {
+        let campaign_info = ctx.accounts.campaign.to_account_info();
+        let new_len = ANCHOR_DISCRIMINATOR_SIZE + Campaign::INIT_SPACE;
+
+        if campaign_info.owner != ctx.program_id {
+            msg!("Campaign not found");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        let old_len = campaign_info.data_len();
+        if old_len >= new_len {
+            msg!("Campaign already migrated");
+            return Ok(());
+        }
+
+        // end of the original layout: discriminator, cid, creator, title,
+        // description, image_url, six u64 counters and `active`
+        let end = {
+            let data = campaign_info.try_borrow_data()?;
+            if data.len() < 48 || data[..8] != Campaign::DISCRIMINATOR {
+                msg!("Account is not a campaign");
+                return Err(ErrorCode::CampaignNotFound.into());
+            }
+            let mut offset = 48;
+            for _ in 0..3 {
+                let len_bytes = data
+                    .get(offset..offset + 4)
+                    .ok_or(ErrorCode::CampaignNotFound)?;
+                let len = u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+                offset += 4 + len;
+            }
+            offset + 6 * 8 + 1
+        };
+        if end > old_len {
+            msg!("Account is not a campaign");
+            return Err(ErrorCode::CampaignNotFound.into());
+        }
+
+        realloc_account(
+            &campaign_info,
+            new_len,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program,
+        )?;
+
+        // leftovers of earlier, longer strings must not be read as new fields
+        campaign_info.try_borrow_mut_data()?[end..].fill(0);
+
+        msg!("Campaign migrated");
+        Ok(())
+    }
+
+    // Grows a donation or withdrawal record created before reward tiers to
+    // the current layout. The added `tier` byte reads as 0, no tier.
+    pub fn migrate_transaction(ctx: Context<MigrateTransactionCtx>) -> Result<()> {
+        let transaction_info = ctx.accounts.transaction.to_account_info();
+
+        if transaction_info.owner != ctx.program_id {
+            msg!("Transaction not found");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        {
+            let data = transaction_info.try_borrow_data()?;
+            if data.len() < 8 || data[..8] != Transaction::DISCRIMINATOR {
+                msg!("Account is not a transaction");
+                return Err(ErrorCode::Unauthorized.into());
+            }
+        }
+
+        realloc_account(
+            &transaction_info,
+            ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program,
+        )?;
+
+        msg!("Transaction migrated");
+        Ok(())
+    }
+
+
+    // ==============================
+    // Blog Site
+    // ==============================
+
+pub fn follow_user(ctx: Context<FollowUser>) -> Result<()> {
+    let follow = &mut ctx.accounts.follow;
+    follow.follower = ctx.accounts.authority.key();
+    follow.followee = ctx.accounts.followee_account.authority;
+    Ok(())
+}
+
+pub fn unfollow_user(_ctx: Context<UnfollowUser>) -> Result<()> {
+    Ok(())
+}
+
+ pub fn init_user(ctx: Context<InitUser>, name: String, avatar: String) -> Result<()> {
+        let user_account = &mut ctx.accounts.user_account;
+        let authority = &ctx.accounts.authority;
+
+        user_account.name = name;
+        user_account.avatar = avatar;
+        user_account.last_post_id = 0;
+        user_account.post_count = 0;
+        user_account.authority = authority.key();
+
+        Ok(())
+    }
+
+  pub fn create_post(
+    ctx: Context<CreatePost>,
+    title: String,
+    content: String,
+    image_url: String,
+) -> Result<()> {
+    let post_account = &mut ctx.accounts.post_account;
+    let user_account = &mut ctx.accounts.user_account;
+    let authority = &ctx.accounts.authority;
+
+    if ctx.accounts.program_state.blog_paused {
+        return Err(ErrorCode::ModulePaused.into());
+    }
+
+    post_account.id = user_account.last_post_id;
+    post_account.title = title.clone();
+    post_account.content = content;
+    post_account.image_url = image_url;
+    post_account.user = user_account.key();
+    post_account.authority = authority.key();
+
+    // Update user stats
+    user_account.last_post_id = user_account
+        .last_post_id
+        .checked_add(1)
+        .unwrap();
+
+    user_account.post_count = user_account
+        .post_count
+        .checked_add(1)
+        .unwrap();
+
+    Ok(())
+}
+
+// FOr update Post 
+
+pub fn update_post(
+    ctx: Context<UpdatePost>,
+    content: String,
+    image_url: String,
+) -> Result<()> {
+    let post_account = &mut ctx.accounts.post_account;
+    let authority = &ctx.accounts.authority;
+
+    require_keys_eq!(post_account.authority, authority.key(), TodoError::Unauthorized);
+
+    // ❌ Don't change the title (it's tied to PDA)
+    post_account.content = content;
+    post_account.image_url = image_url;
+
+    Ok(())
+}
+pub fn delete_post(ctx: Context<DeletePost>, title: String) -> Result<()> {
+    let post_account = &mut ctx.accounts.post_account;
+    let user_account = &mut ctx.accounts.user_account;
+    let authority = &ctx.accounts.authority;
+
+    require_keys_eq!(post_account.authority, authority.key(),  TodoError::Unauthorized);
+
+    // Decrease post count
+    user_account.post_count = user_account
+        .post_count
+        .checked_sub(1)
+        .unwrap();
+
+    // Close the account
+    Ok(post_account.close(authority.to_account_info())?)
+}
+
+// Clears whatever an older, longer content or image URL left after the
+// original post fields, so `poll` reads as None. A poll really attached by
+// the author is kept when passed in as `poll`.
+pub fn migrate_post(ctx: Context<MigratePost>, _title: String) -> Result<()> {
+    let post_info = ctx.accounts.post_account.to_account_info();
+    let authority = ctx.accounts.authority.key();
+
+    require_keys_eq!(*post_info.owner, *ctx.program_id, TodoError::Unauthorized);
+
+    // end of the original layout: discriminator, id, title, content,
+    // image_url, user and authority
+    let end = {
+        let data = post_info.try_borrow_data()?;
+        if data.len() < 9 || data[..8] != PostAccount::DISCRIMINATOR {
+            return Err(TodoError::Unauthorized.into());
+        }
+        let mut offset = 9;
+        for _ in 0..3 {
+            let len_bytes = data
+                .get(offset..offset + 4)
+                .ok_or(TodoError::Unauthorized)?;
+            let len = u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+            offset += 4 + len;
+        }
+        offset + 2 * 32
+    };
+    if end + 33 > post_info.data_len() {
+        return Err(TodoError::Unauthorized.into());
+    }
+
+    let attached = ctx
+        .accounts
+        .poll
+        .as_ref()
+        .filter(|poll| poll.creator == authority)
+        .map(|poll| poll.key());
+
+    let mut data = post_info.try_borrow_mut_data()?;
+    let keep = attached.filter(|poll| data[end] == 1 && data[end + 1..end + 33] == poll.to_bytes());
+    data[end..].fill(0);
+    if let Some(poll) = keep {
+        data[end] = 1;
+        data[end + 1..end + 33].copy_from_slice(&poll.to_bytes());
+    }
+
+    Ok(())
+}
+
+
+//
+// ==============================
+//  Chat Dapp Function
+// ==============================
+//
+
+pub fn initialize_thread(ctx: Context<InitializeThread>) -> Result<()> {
+        let thread = &mut ctx.accounts.thread;
+        thread.sender = ctx.accounts.sender.key();
+        thread.recipient = ctx.accounts.recipient.key();
+        Ok(())
+    }
+
+    pub fn send_message(ctx: Context<SendMessage>, content: String, timestamp: i64) -> Result<()> {
+        let message = &mut ctx.accounts.message;
+        message.sender = ctx.accounts.sender.key();
+        message.thread = ctx.accounts.thread.key();
+        message.content = content;
+        message.timestamp = timestamp;
+        Ok(())
+    }
+
+
+
+
+}
+
+// ==============================
+// Helpers
+// ==============================
+
+// Allows closing a poll's side accounts once the poll is closable, or at any
+// time after the poll account itself is closed.
+fn check_poll_closable(poll: &UncheckedAccount) -> Result<()> {
+    if poll.data_is_empty() {
+        return Ok(());
+    }
+
+    let data = poll.try_borrow_data()?;
+    let poll = Poll::try_deserialize(&mut &data[..])?;
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    if !poll.is_closable(current_timestamp) {
+        return Err(errors::VoteError::PollNotClosable.into());
+    }
+
+    Ok(())
+}
+
+// Part of a SOL platform fee owed to the conviction treasury, none until the
+// treasury is initialized
+fn treasury_fee_share(treasury: &UncheckedAccount, program_id: &Pubkey, platform_fee: u64) -> Result<u64> {
+    if treasury.owner != program_id || treasury.data_is_empty() {
+        return Ok(0);
+    }
+
+    let data = treasury.try_borrow_data()?;
+    let treasury = Treasury::try_deserialize(&mut &data[..])?;
+    Ok(treasury.fee_share(platform_fee))
+}
+
+// Checks the voter against the poll's Merkle allowlist and returns the weight
+// it grants (1 when the poll is open or the allowlist is unweighted).
+fn check_eligibility(
+    poll: &Poll,
+    voter: &Pubkey,
+    eligibility: &Option<EligibilityProof>,
+    audience_record: Option<&UncheckedAccount>,
+) -> Result<u64> {
+    if poll.nft_collection.is_some() {
+        return Err(errors::VoteError::NftRequired.into());
+    }
+
+    // polls scoped to followers or donors need the voter's record for the author or campaign
+    let in_audience = match (poll.audience, audience_record) {
+        (PollAudience::Anyone, _) => true,
+        (_, Some(record)) if record.owner != &crate::ID => false,
+        (PollAudience::Followers { author }, Some(record)) => {
+            let data = record.try_borrow_data()?;
+            let follow = Follow::try_deserialize(&mut &data[..])?;
+            follow.follower == *voter && follow.followee == author
+        }
+        (PollAudience::Donors { cid }, Some(record)) => {
+            let data = record.try_borrow_data()?;
+            let transaction = Transaction::try_deserialize(&mut &data[..])?;
+            transaction.owner == *voter && transaction.cid == cid && transaction.credited
+        }
+        (_, None) => false,
+    };
+    if !in_audience {
+        return Err(errors::VoteError::NotEligible.into());
+    }
+
+    let root = match poll.voter_root {
+        Some(root) => root,
+        None => return Ok(1),
+    };
+
+    let eligibility = match eligibility {
+        Some(eligibility) => eligibility,
+        None => return Err(errors::VoteError::NotEligible.into()),
+    };
+
+    if !eligibility.verify(voter, poll.weighted_root, &root) {
+        return Err(errors::VoteError::NotEligible.into());
+    }
+
+    if !poll.weighted_root {
+        return Ok(1);
+    }
+    if eligibility.weight == 0 {
+        return Err(errors::VoteError::NoVotingPower.into());
+    }
+    Ok(eligibility.weight)
+}
+
+// Grows an account written under an older, shorter layout to `new_len`,
+// topping its rent up from `payer`. New bytes are zeroed.
+// Supply of the governance mint, if `mint` is the one the platform set
+fn governance_supply(state: &ProgramState, mint: &Option<Account<Mint>>) -> Option<u64> {
+    match mint {
+        Some(mint) if Some(mint.key()) == state.governance_mint => Some(mint.supply),
+        _ => None,
+    }
+}
+
+fn realloc_account<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if account.data_len() >= new_len {
+        return Ok(());
+    }
+
+    let top_up = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+
+    account.realloc(new_len, true)?;
+    Ok(())
+}
+
+// Creates a program-owned PDA from the remaining accounts, coping with
+// lamports that were sent to the address beforehand (like Anchor's `init`).
+fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+    system_program: &Program<'info, System>,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = target.lamports();
+    let signer: &[&[&[u8]]] = &[signer_seeds];
+
+    if current_lamports == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+                signer,
+            ),
+            rent,
+            space as u64,
+            program_id,
+        );
+    }
+
+    if rent > current_lamports {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+            ),
+            rent - current_lamports,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Allocate {
+                account_to_allocate: target.clone(),
+            },
+            signer,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Assign {
+                account_to_assign: target.clone(),
+            },
+            signer,
+        ),
+        program_id,
+    )
+}
+
+// ==============================
+// Contexts
+// ==============================
+
+// Notes
+#[derive(Accounts)]
+#[instruction(title: String)]
+pub struct CreateNote<'info> {
+    #[account(
+        init,
+        seeds = [NOTE_SEED, author.key().as_ref(), title.as_bytes()],
+        bump,
+        payer = author,
+        space = 8 + NoteAccount::INIT_SPACE,
+    )]
+    pub note_account: Account<'info, NoteAccount>,
+
+    #[account(mut)]
+    pub author: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateNote<'info> {
+    #[account(
+        mut,
+        seeds = [NOTE_SEED, author.key().as_ref(), note_account.title.as_bytes()],
+        bump,
+    )]
+    pub note_account: Account<'info, NoteAccount>,
+
+    pub author: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeleteNote<'info> {
+    #[account(
+        mut,
+        seeds = [NOTE_SEED, author.key().as_ref(), note_account.title.as_bytes()],
+        bump,
+        close = author
+    )]
+    pub note_account: Account<'info, NoteAccount>,
+
+    #[account(mut)]
+    pub author: Signer<'info>,
+}
+
+// TODO
+#[derive(Accounts)]
+#[instruction(task_title: String)]
+pub struct CreateTask<'info> {
+    #[account(
+        init,
+        seeds = [TODO_SEED, author.key().as_ref(), task_title.as_bytes()],
+        bump,
+        payer = author,
+        space = 8 + TodoAccount::INIT_SPACE,
+    )]
+    pub todo_account: Account<'info, TodoAccount>,
+
+    #[account(mut)]
+    pub author: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MarkCompleted<'info> {
+    #[account(
+        mut,
+        seeds = [TODO_SEED, author.key().as_ref(), todo_account.task_title.as_bytes()],
+        bump,
+    )]
+    pub todo_account: Account<'info, TodoAccount>,
+
+    pub author: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeleteTask<'info> {
+    #[account(
+        mut,
+        seeds = [TODO_SEED, author.key().as_ref(), todo_account.task_title.as_bytes()],
+        bump,
+        close = author
+    )]
+    pub todo_account: Account<'info, TodoAccount>,
+
+    #[account(mut)]
+    pub author: Signer<'info>,
+}
+
+
+
+
+//Chat App
+
+
+
+
+
+
+
+
+// Crowdfunding
+
+#[derive(Accounts)]
+pub struct InitializeCtx<'info>{
+    #[account(
+        init,
+        payer = deployer,
+        space = 8 + ProgramState::INIT_SPACE,
+        seeds=[b"program_state"],
+        bump
+    )]
+    pub program_state:Account<'info,ProgramState>,
+    #[account(mut)]
+    pub deployer :Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCampaignCtx<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Campaign::INIT_SPACE,
+        seeds = [
+            b"campaign",
+            (program_state.campaign_count + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    // Token campaigns only: the mint and its vault, owned by the vault PDA itself
+    pub mint: Option<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [
+            b"campaign_vault",
+            (program_state.campaign_count + 1).to_le_bytes().as_ref()
+        ],
+        bump,
+        token::mint = mint,
+        token::authority = vault,
+    )]
+    pub vault: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct UpdateCampaignCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct DeleteCampaignCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct DonateCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = donor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [
+            b"donor",
+            donor.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            (campaign.donors + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct WithdrawCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [
+            b"withdraw",
+            creator.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            (campaign.withdrawals + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// CHECK: this is the platform's account which must match program_state.platform_address
+    #[account(mut)]
+    pub platform_address: AccountInfo<'info>,
+
+    /// CHECK: the conviction treasury, may not be initialized; read in the handler
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64, donation: u64)]
+pub struct ClaimRefundCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    // the donation record is closed so it cannot be refunded twice
+    #[account(
+        mut,
+        seeds = [
+            b"donor",
+            donor.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            donation.to_le_bytes().as_ref()
+        ],
+        bump,
+        close = donor
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct DonateTokenCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = donor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [
+            b"donor",
+            donor.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            (campaign.donors + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign_vault", cid.to_le_bytes().as_ref()],
+        bump,
+        constraint = Some(vault.mint) == campaign.mint @ ErrorCode::WrongCurrency,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.mint,
+        token::authority = donor,
+    )]
+    pub donor_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct WithdrawTokenCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
+        seeds = [
+            b"withdraw",
+            creator.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            (campaign.withdrawals + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign_vault", cid.to_le_bytes().as_ref()],
+        bump,
+        constraint = Some(vault.mint) == campaign.mint @ ErrorCode::WrongCurrency,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.mint,
+        token::authority = creator,
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.mint,
+        token::authority = program_state.platform_address,
+    )]
+    pub platform_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64, donation: u64)]
+pub struct ClaimTokenRefundCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"donor",
+            donor.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            donation.to_le_bytes().as_ref()
+        ],
+        bump,
+        close = donor
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign_vault", cid.to_le_bytes().as_ref()],
+        bump,
+        constraint = Some(vault.mint) == campaign.mint @ ErrorCode::WrongCurrency,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = vault.mint,
+        token::authority = donor,
+    )]
+    pub donor_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct AddRewardTierCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct RequestMilestoneApprovalCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64, index: u8, donation: u64)]
+pub struct VoteMilestoneCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [
+            b"donor",
+            donor.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            donation.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    #[account(
+        init,
+        payer = donor,
+        space = ANCHOR_DISCRIMINATOR_SIZE + MilestoneVote::INIT_SPACE,
+        seeds = [
+            b"milestone_vote",
+            cid.to_le_bytes().as_ref(),
+            [index].as_ref(),
+            transaction.key().as_ref()
+        ],
+        bump
+    )]
+    pub milestone_vote: Account<'info, MilestoneVote>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct FinalizeMilestoneCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64, donation: u64)]
+pub struct ClaimMilestoneRefundCtx<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    // the donation record is closed so it cannot be refunded twice
+    #[account(
+        mut,
+        seeds = [
+            b"donor",
+            donor.key().as_ref(),
+            cid.to_le_bytes().as_ref(),
+            donation.to_le_bytes().as_ref()
+        ],
+        bump,
+        close = donor
+    )]
+    pub transaction: Account<'info, Transaction>,
+
+    // Token campaigns only
+    #[account(
+        mut,
+        seeds = [b"campaign_vault", cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::authority = donor,
+    )]
+    pub donor_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProgramStateCtx<'info> {
+    /// CHECK: may still have the old, shorter layout; checked in the handler
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64)]
+pub struct MigrateCampaignCtx<'info> {
+    /// CHECK: may still have the old, shorter layout; checked in the handler
+    #[account(
+        mut,
+        seeds = [b"campaign", cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub campaign: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateTransactionCtx<'info> {
+    /// CHECK: may still have the old, shorter layout; checked in the handler
+    #[account(mut)]
+    pub transaction: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlatformSettingsCtx<'info> {
+    #[account(mut)]
+    pub updater: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+// ==============================
+// Vote Dapp Context
+// ==============================
+#[derive(Accounts)]
+#[instruction(poll_id: u64, description: String, start: u64, end: u64)]
+pub struct CreatePoll<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"counter"],
+        bump,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Poll::INIT_SPACE,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()], // Use the poll_id from the instruction
+        bump,
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + std::mem::size_of::<PollTally>(),
+        seeds = [b"tally", poll_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub tally: AccountLoader<'info, PollTally>,
+
+    pub system_program: Program<'info, System>,
+}
+
+
+#[derive(Accounts)]
+pub struct InitializeVote<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+      init,
+      payer = user,
+      space = ANCHOR_DISCRIMINATOR_SIZE + 8,
+      seeds = [b"counter"],
+      bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub system_program: Program<'info, System>,
+}
+
+//Register COntex fro vote  Dapp
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct RegisterCandidate<'info> {
+    #[account(
+        mut,
+        seeds = [ b"poll" ,poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        init, // Create the voter account if it doesn't exist
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Candidate::INIT_SPACE, // Account size
+        seeds = [
+            b"candidate",
+            poll_id.to_le_bytes().as_ref(),
+            (poll.candidates + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub candidate: Account<'info, Candidate>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // Only required when the poll uses `RegistrationPolicy::Allowlist`
+    #[account(
+        mut,
+        seeds = [b"registrant", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub registration_pass: Option<Account<'info, RegistrationPass>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct SetNominationDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64, cid: u64)]
+pub struct WithdrawCandidacy<'info> {
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub candidate: Account<'info, Candidate>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64, cid: u64)]
+pub struct UpdateCandidateProfile<'info> {
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub candidate: Account<'info, Candidate>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64, cid: u64)]
+pub struct SettleNominationDeposit<'info> {
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub candidate: Account<'info, Candidate>,
+
+    // Ranked-choice polls only
+    #[account(
+        seeds = [b"ranked_results", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub ranked_results: Option<Account<'info, RankedResults>>,
+
+    /// CHECK: receives a refunded deposit, must be the wallet that registered the candidate
+    #[account(mut, address = candidate.authority)]
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: receives a forfeited deposit, must be the poll creator
+    #[account(mut, address = poll.creator)]
+    pub creator: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct SetProposalAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    // required unless the poll creator is the platform authority
+    pub governance_mint: Option<Account<'info, Mint>>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    // required unless the poll creator is the platform authority
+    pub governance_mint: Option<Account<'info, Mint>>,
+}
+
+#[derive(Accounts)]
+#[instruction(title: String, poll_id: u64)]
+pub struct AttachPollToPost<'info> {
+    #[account(
+        mut,
+        seeds = [POST_SEED, authority.key().as_ref(), title.as_bytes()],
+        bump,
+        has_one = authority
+    )]
+    pub post_account: Account<'info, PostAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(cid: u64, poll_id: u64)]
+pub struct AttachPollToCampaign<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"campaign",
+            cid.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct CancelPoll<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct ExtendPoll<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct SetPollRules<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct SetNftCollection<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64, cid: u64)]
+pub struct VoteWithNft<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"tally", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tally: AccountLoader<'info, PollTally>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub candidate: Account<'info, Candidate>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + NftVoteReceipt::INIT_SPACE,
+        seeds = [b"nft_vote", poll_id.to_le_bytes().as_ref(), nft_mint.key().as_ref()],
+        bump
+    )]
+    pub receipt: Account<'info, NftVoteReceipt>,
+
+    pub nft_mint: Account<'info, Mint>,
+
+    #[account(
+        token::mint = nft_mint,
+        token::authority = user,
+    )]
+    pub nft_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"metadata", Metadata::id().as_ref(), nft_mint.key().as_ref()],
+        seeds::program = Metadata::id(),
+        bump
+    )]
+    pub nft_metadata: Account<'info, MetadataAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct SetVoterRoot<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64, wallet: Pubkey)]
+pub struct AllowCandidate<'info> {
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + RegistrationPass::INIT_SPACE,
+        seeds = [b"registrant", poll_id.to_le_bytes().as_ref(), wallet.as_ref()],
+        bump
+    )]
+    pub registration_pass: Account<'info, RegistrationPass>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+
+// Vote Context
+#[derive(Accounts)]
+#[instruction(poll_id: u64, cid: u64)]
+pub struct Vote<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll" ,poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>, // Poll to be voted in
+
+    #[account(
+        mut,
+        seeds = [b"tally", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tally: AccountLoader<'info, PollTally>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub candidate: Account<'info, Candidate>, // Candidate to receive the vote
+
+    #[account(
+        init, // Create the voter account if it doesn't exist
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Voter::INIT_SPACE, // Account size
+        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub voter: Account<'info, Voter>, // Unique per poll and user
+
+    #[account(mut)]
+    pub user: Signer<'info>, // Voter's signer account
+
+    // Token-weighted polls only
+    pub mint: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub voter_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Escrowed token-weighted polls only: holds the voter's tokens until the poll ends
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"vote_escrow", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = vote_escrow,
+    )]
+    pub vote_escrow: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+
+    // Follow or donation record of the voter when the poll's audience is restricted
+    /// CHECK: owner and contents are verified in `check_eligibility`
+    pub audience_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct VoteRanked<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Voter::INIT_SPACE,
+        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub voter: Account<'info, Voter>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // Follow or donation record of the voter when the poll's audience is restricted
+    /// CHECK: owner and contents are verified in `check_eligibility`
+    pub audience_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct VoteApproval<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"tally", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tally: AccountLoader<'info, PollTally>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Voter::INIT_SPACE,
+        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub voter: Account<'info, Voter>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // Follow or donation record of the voter when the poll's audience is restricted
+    /// CHECK: owner and contents are verified in `check_eligibility`
+    pub audience_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64, cid: u64)]
+pub struct VoteQuadratic<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"tally", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tally: AccountLoader<'info, PollTally>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub candidate: Account<'info, Candidate>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Voter::INIT_SPACE,
+        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub voter: Account<'info, Voter>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // Follow or donation record of the voter when the poll's audience is restricted
+    /// CHECK: owner and contents are verified in `check_eligibility`
+    pub audience_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct AddSurveyQuestion<'info> {
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + SurveyResults::INIT_SPACE,
+        seeds = [b"survey", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub survey: Account<'info, SurveyResults>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct VoteSurvey<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"survey", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub survey: Account<'info, SurveyResults>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Voter::INIT_SPACE,
+        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub voter: Account<'info, Voter>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // Follow or donation record of the voter when the poll's audience is restricted
+    /// CHECK: owner and contents are verified in `check_eligibility`
+    pub audience_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct CommitVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        init,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Voter::INIT_SPACE,
+        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub voter: Account<'info, Voter>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // Follow or donation record of the voter when the poll's audience is restricted
+    /// CHECK: owner and contents are verified in `check_eligibility`
+    pub audience_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64, cid: u64)]
+pub struct RevealVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"tally", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tally: AccountLoader<'info, PollTally>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub candidate: Account<'info, Candidate>,
+
+    #[account(
+        mut,
+        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub voter: Account<'info, Voter>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct FinalizePoll<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        seeds = [b"tally", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tally: AccountLoader<'info, PollTally>,
+
+    // Ranked-choice polls only
+    #[account(
+        seeds = [b"ranked_results", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub ranked_results: Option<Account<'info, RankedResults>>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct CloseVoter<'info> {
+    /// CHECK: may already be closed; deserialized in the handler when it still exists
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump,
+        close = rent_payer
+    )]
+    pub voter: Account<'info, Voter>,
 
-    user_account.post_count = user_account
-        .post_count
-        .checked_add(1)
-        .unwrap();
+    /// CHECK: receives the rent, must be whoever paid for the voter account
+    #[account(mut, address = voter.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
 
-    Ok(())
+    pub user: Signer<'info>,
 }
 
-// FOr update Post 
-
-pub fn update_post(
-    ctx: Context<UpdatePost>,
-    content: String,
-    image_url: String,
-) -> Result<()> {
-    let post_account = &mut ctx.accounts.post_account;
-    let authority = &ctx.accounts.authority;
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct CloseRankedResults<'info> {
+    /// CHECK: may already be closed; deserialized in the handler when it still exists
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: UncheckedAccount<'info>,
 
-    require_keys_eq!(post_account.authority, authority.key(), TodoError::Unauthorized);
+    #[account(
+        mut,
+        seeds = [b"ranked_results", poll_id.to_le_bytes().as_ref()],
+        bump,
+        close = rent_payer
+    )]
+    pub results: Account<'info, RankedResults>,
 
-    // ❌ Don't change the title (it's tied to PDA)
-    post_account.content = content;
-    post_account.image_url = image_url;
+    /// CHECK: receives the rent, must be whoever paid for the results account
+    #[account(mut, address = results.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
 
-    Ok(())
+    pub user: Signer<'info>,
 }
-pub fn delete_post(ctx: Context<DeletePost>, title: String) -> Result<()> {
-    let post_account = &mut ctx.accounts.post_account;
-    let user_account = &mut ctx.accounts.user_account;
-    let authority = &ctx.accounts.authority;
 
-    require_keys_eq!(post_account.authority, authority.key(),  TodoError::Unauthorized);
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct CloseSurvey<'info> {
+    /// CHECK: may already be closed; deserialized in the handler when it still exists
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: UncheckedAccount<'info>,
 
-    // Decrease post count
-    user_account.post_count = user_account
-        .post_count
-        .checked_sub(1)
-        .unwrap();
+    #[account(
+        mut,
+        seeds = [b"survey", poll_id.to_le_bytes().as_ref()],
+        bump,
+        close = rent_payer
+    )]
+    pub survey: Account<'info, SurveyResults>,
 
-    // Close the account
-    Ok(post_account.close(authority.to_account_info())?)
+    /// CHECK: receives the rent, must be whoever paid for the survey account
+    #[account(mut, address = survey.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    pub user: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(poll_id: u64, mint: Pubkey)]
+pub struct CloseNftReceipt<'info> {
+    /// CHECK: may already be closed; deserialized in the handler when it still exists
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: UncheckedAccount<'info>,
 
-//
-// ==============================
-//  Chat Dapp Function
-// ==============================
-//
+    #[account(
+        mut,
+        seeds = [b"nft_vote", poll_id.to_le_bytes().as_ref(), mint.as_ref()],
+        bump,
+        close = voter
+    )]
+    pub receipt: Account<'info, NftVoteReceipt>,
 
-pub fn initialize_thread(ctx: Context<InitializeThread>) -> Result<()> {
-        let thread = &mut ctx.accounts.thread;
-        thread.sender = ctx.accounts.sender.key();
-        thread.recipient = ctx.accounts.recipient.key();
-        Ok(())
-    }
+    /// CHECK: receives the rent, must be the wallet that voted with the NFT
+    #[account(mut, address = receipt.voter)]
+    pub voter: UncheckedAccount<'info>,
 
-    pub fn send_message(ctx: Context<SendMessage>, content: String, timestamp: i64) -> Result<()> {
-        let message = &mut ctx.accounts.message;
-        message.sender = ctx.accounts.sender.key();
-        message.thread = ctx.accounts.thread.key();
-        message.content = content;
-        message.timestamp = timestamp;
-        Ok(())
-    }
+    pub user: Signer<'info>,
+}
 
+#[derive(Accounts)]
+#[instruction(poll_id: u64, cid: u64)]
+pub struct CloseCandidate<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
 
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), cid.to_le_bytes().as_ref()],
+        bump,
+        close = authority
+    )]
+    pub candidate: Account<'info, Candidate>,
 
+    /// CHECK: receives the rent, must be the wallet that registered the candidate
+    #[account(mut, address = candidate.authority)]
+    pub authority: UncheckedAccount<'info>,
 
+    pub user: Signer<'info>,
 }
 
-// ==============================
-// Contexts
-// ==============================
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct ClosePoll<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump,
+        close = user
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"tally", poll_id.to_le_bytes().as_ref()],
+        bump,
+        close = user
+    )]
+    pub tally: AccountLoader<'info, PollTally>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
 
-// Notes
 #[derive(Accounts)]
-#[instruction(title: String)]
-pub struct CreateNote<'info> {
+#[instruction(poll_id: u64)]
+pub struct TallyRanked<'info> {
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ANCHOR_DISCRIMINATOR_SIZE + RankedResults::INIT_SPACE,
+        seeds = [b"ranked_results", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub results: Account<'info, RankedResults>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(scope: DelegationScope)]
+pub struct DelegateVote<'info> {
     #[account(
         init,
-        seeds = [NOTE_SEED, author.key().as_ref(), title.as_bytes()],
-        bump,
-        payer = author,
-        space = 8 + NoteAccount::INIT_SPACE,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Delegation::INIT_SPACE,
+        seeds = [b"delegation", user.key().as_ref(), scope.seed().as_ref()],
+        bump
     )]
-    pub note_account: Account<'info, NoteAccount>,
+    pub delegation: Account<'info, Delegation>,
 
     #[account(mut)]
-    pub author: Signer<'info>,
+    pub user: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateNote<'info> {
+#[instruction(scope: DelegationScope)]
+pub struct UndelegateVote<'info> {
     #[account(
         mut,
-        seeds = [NOTE_SEED, author.key().as_ref(), note_account.title.as_bytes()],
+        seeds = [b"delegation", user.key().as_ref(), scope.seed().as_ref()],
         bump,
+        close = user
     )]
-    pub note_account: Account<'info, NoteAccount>,
+    pub delegation: Account<'info, Delegation>,
 
-    pub author: Signer<'info>,
+    #[account(mut)]
+    pub user: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct DeleteNote<'info> {
+#[instruction(poll_id: u64, cid: u64)]
+pub struct OverrideDelegation<'info> {
     #[account(
         mut,
-        seeds = [NOTE_SEED, author.key().as_ref(), note_account.title.as_bytes()],
-        bump,
-        close = author
+        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
     )]
-    pub note_account: Account<'info, NoteAccount>,
+    pub voter: Account<'info, Voter>,
 
-    #[account(mut)]
-    pub author: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"voter",
+            poll_id.to_le_bytes().as_ref(),
+            voter.delegate.unwrap_or_default().as_ref()
+        ],
+        bump
+    )]
+    pub delegate_voter: Account<'info, Voter>,
+
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"tally", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tally: AccountLoader<'info, PollTally>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), delegate_voter.cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub current_candidate: Account<'info, Candidate>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub new_candidate: Account<'info, Candidate>,
+
+    pub user: Signer<'info>,
 }
 
-// TODO
 #[derive(Accounts)]
-#[instruction(task_title: String)]
-pub struct CreateTask<'info> {
+#[instruction(poll_id: u64, new_cid: u64)]
+pub struct ChangeVote<'info> {
     #[account(
-        init,
-        seeds = [TODO_SEED, author.key().as_ref(), task_title.as_bytes()],
+        mut,
+        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub voter: Account<'info, Voter>,
+
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"tally", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tally: AccountLoader<'info, PollTally>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), voter.cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub current_candidate: Account<'info, Candidate>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), new_cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub new_candidate: Account<'info, Candidate>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(poll_id: u64)]
+pub struct RevokeVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
         bump,
-        payer = author,
-        space = 8 + TodoAccount::INIT_SPACE,
+        close = user
     )]
-    pub todo_account: Account<'info, TodoAccount>,
+    pub voter: Account<'info, Voter>,
+
+    #[account(
+        mut,
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        mut,
+        seeds = [b"tally", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tally: AccountLoader<'info, PollTally>,
+
+    #[account(
+        mut,
+        seeds = [b"candidate", poll_id.to_le_bytes().as_ref(), voter.cid.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub candidate: Account<'info, Candidate>,
 
     #[account(mut)]
-    pub author: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    pub user: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct MarkCompleted<'info> {
+#[instruction(poll_id: u64)]
+pub struct ReleaseVoteEscrow<'info> {
+    /// CHECK: may already be closed; deserialized in the handler when it still exists
+    #[account(
+        seeds = [b"poll", poll_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub poll: UncheckedAccount<'info>,
+
     #[account(
         mut,
-        seeds = [TODO_SEED, author.key().as_ref(), todo_account.task_title.as_bytes()],
-        bump,
+        seeds = [b"vote_escrow", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump
     )]
-    pub todo_account: Account<'info, TodoAccount>,
+    pub vote_escrow: Account<'info, TokenAccount>,
 
-    pub author: Signer<'info>,
-}
-
-#[derive(Accounts)]
-pub struct DeleteTask<'info> {
     #[account(
         mut,
-        seeds = [TODO_SEED, author.key().as_ref(), todo_account.task_title.as_bytes()],
-        bump,
-        close = author
+        token::mint = vote_escrow.mint,
+        token::authority = user,
     )]
-    pub todo_account: Account<'info, TodoAccount>,
+    pub voter_token_account: Account<'info, TokenAccount>,
 
     #[account(mut)]
-    pub author: Signer<'info>,
-}
-
-
+    pub user: Signer<'info>,
 
+    pub token_program: Program<'info, Token>,
+}
 
-//Chat App
 
 
 
@@ -716,300 +5115,225 @@ pub struct DeleteTask<'info> {
 
 
 
-// Crowdfunding
 
 #[derive(Accounts)]
-pub struct InitializeCtx<'info>{
+pub struct InitializeTreasury<'info> {
     #[account(
-        init,
-        payer = deployer,
-        space = 8 + ProgramState::INIT_SPACE,
-        seeds=[b"program_state"],
+        seeds = [b"program_state"],
         bump
     )]
-    pub program_state:Account<'info,ProgramState>,
-    #[account(mut)]
-    pub deployer :Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct CreateCampaignCtx<'info> {
-    #[account(mut)]
     pub program_state: Account<'info, ProgramState>,
 
     #[account(
         init,
-        payer = creator,
-        space = ANCHOR_DISCRIMINATOR_SIZE + Campaign::INIT_SPACE,
-        seeds = [
-            b"campaign",
-            (program_state.campaign_count + 1).to_le_bytes().as_ref()
-        ],
+        payer = authority,
+        space = ANCHOR_DISCRIMINATOR_SIZE + Treasury::INIT_SPACE,
+        seeds = [b"treasury"],
         bump
     )]
-    pub campaign: Account<'info, Campaign>,
-
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+    pub treasury: Account<'info, Treasury>,
 
-#[derive(Accounts)]
-#[instruction(cid: u64)]
-pub struct UpdateCampaignCtx<'info> {
-    #[account(
-        mut,
-        seeds = [
-            b"campaign",
-            cid.to_le_bytes().as_ref()
-        ],
-        bump
-    )]
-    pub campaign: Account<'info, Campaign>,
+    pub stake_mint: Account<'info, Mint>,
 
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub authority: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(cid: u64)]
-pub struct DeleteCampaignCtx<'info> {
+pub struct FundTreasury<'info> {
     #[account(
         mut,
-        seeds = [
-            b"campaign",
-            cid.to_le_bytes().as_ref()
-        ],
+        seeds = [b"treasury"],
         bump
     )]
-    pub campaign: Account<'info, Campaign>,
+    pub treasury: Account<'info, Treasury>,
 
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub funder: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(cid: u64)]
-pub struct DonateCtx<'info> {
+#[instruction(proposal_id: u64)]
+pub struct CreateFundingProposal<'info> {
     #[account(
         mut,
-        seeds = [
-            b"campaign",
-            cid.to_le_bytes().as_ref()
-        ],
+        seeds = [b"treasury"],
         bump
     )]
-    pub campaign: Account<'info, Campaign>,
+    pub treasury: Account<'info, Treasury>,
 
     #[account(
         init,
-        payer = donor,
-        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
-        seeds = [
-            b"donor",
-            donor.key().as_ref(),
-            cid.to_le_bytes().as_ref(),
-            (campaign.donors + 1).to_le_bytes().as_ref()
-        ],
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + FundingProposal::INIT_SPACE,
+        seeds = [b"funding_proposal", proposal_id.to_le_bytes().as_ref()],
         bump
     )]
-    pub transaction: Account<'info, Transaction>,
+    pub proposal: Account<'info, FundingProposal>,
 
     #[account(mut)]
-    pub donor: Signer<'info>,
+    pub user: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(cid: u64)]
-pub struct WithdrawCtx<'info> {
+#[instruction(proposal_id: u64)]
+pub struct StakeConviction<'info> {
     #[account(
         mut,
-        seeds = [
-            b"campaign",
-            cid.to_le_bytes().as_ref()
-        ],
+        seeds = [b"treasury"],
         bump
     )]
-    pub campaign: Account<'info, Campaign>,
+    pub treasury: Account<'info, Treasury>,
 
     #[account(
-        init,
-        payer = creator,
-        space = ANCHOR_DISCRIMINATOR_SIZE + Transaction::INIT_SPACE,
-        seeds = [
-            b"withdraw",
-            creator.key().as_ref(),
-            cid.to_le_bytes().as_ref(),
-            (campaign.withdrawals + 1).to_le_bytes().as_ref()
-        ],
+        mut,
+        seeds = [b"funding_proposal", proposal_id.to_le_bytes().as_ref()],
         bump
     )]
-    pub transaction: Account<'info, Transaction>,
-
-    #[account(mut)]
-    pub program_state: Account<'info, ProgramState>,
-
-    /// CHECK: this is the platform's account which must match program_state.platform_address
-    #[account(mut)]
-    pub platform_address: AccountInfo<'info>,
-
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct UpdatePlatformSettingsCtx<'info> {
-    #[account(mut)]
-    pub updater: Signer<'info>,
+    pub proposal: Account<'info, FundingProposal>,
 
     #[account(
-        mut,
-        seeds = [b"program_state"],
+        init_if_needed,
+        payer = user,
+        space = ANCHOR_DISCRIMINATOR_SIZE + ConvictionStake::INIT_SPACE,
+        seeds = [b"conviction_stake", proposal_id.to_le_bytes().as_ref(), user.key().as_ref()],
         bump
     )]
-    pub program_state: Account<'info, ProgramState>,
-}
+    pub stake: Account<'info, ConvictionStake>,
 
-// ==============================
-// Vote Dapp Context
-// ==============================
-#[derive(Accounts)]
-#[instruction(poll_id: u64, description: String, start: u64, end: u64)]
-pub struct CreatePoll<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
+    #[account(address = treasury.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
 
     #[account(
         mut,
-        seeds = [b"counter"],
-        bump,
+        token::mint = stake_mint,
+        token::authority = user,
     )]
-    pub counter: Account<'info, Counter>,
+    pub staker_token_account: Account<'info, TokenAccount>,
 
     #[account(
-        init,
+        init_if_needed,
         payer = user,
-        space = ANCHOR_DISCRIMINATOR_SIZE + Poll::INIT_SPACE,
-        seeds = [b"poll", poll_id.to_le_bytes().as_ref()], // Use the poll_id from the instruction
+        seeds = [b"conviction_escrow", proposal_id.to_le_bytes().as_ref(), user.key().as_ref()],
         bump,
+        token::mint = stake_mint,
+        token::authority = stake_escrow,
     )]
-    pub poll: Account<'info, Poll>,
+    pub stake_escrow: Account<'info, TokenAccount>,
 
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
-
 #[derive(Accounts)]
-pub struct InitializeVote<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-
+#[instruction(proposal_id: u64)]
+pub struct UnstakeConviction<'info> {
     #[account(
-      init,
-      payer = user,
-      space = ANCHOR_DISCRIMINATOR_SIZE + 8,
-      seeds = [b"counter"],
-      bump
+        mut,
+        seeds = [b"treasury"],
+        bump
     )]
-    pub counter: Account<'info, Counter>,
+    pub treasury: Account<'info, Treasury>,
 
     #[account(
-      init,
-      payer = user,
-      space = ANCHOR_DISCRIMINATOR_SIZE + 8,
-      seeds = [b"registerations"],
-      bump
+        mut,
+        seeds = [b"funding_proposal", proposal_id.to_le_bytes().as_ref()],
+        bump
     )]
-    pub registerations: Account<'info, Registerations>,
-
-    pub system_program: Program<'info, System>,
-}
+    pub proposal: Account<'info, FundingProposal>,
 
-//Register COntex fro vote  Dapp
-#[derive(Accounts)]
-#[instruction(poll_id: u64)]
-pub struct RegisterCandidate<'info> {
     #[account(
         mut,
-        seeds = [ b"poll" ,poll_id.to_le_bytes().as_ref()],
+        seeds = [b"conviction_stake", proposal_id.to_le_bytes().as_ref(), user.key().as_ref()],
         bump
     )]
-    pub poll: Account<'info, Poll>,
+    pub stake: Account<'info, ConvictionStake>,
 
     #[account(
-        init, // Create the voter account if it doesn't exist
-        payer = user,
-        space = ANCHOR_DISCRIMINATOR_SIZE + Candidate::INIT_SPACE, // Account size
-        seeds = [
-            poll_id.to_le_bytes().as_ref(),
-            (registerations.count + 1).to_le_bytes().as_ref()
-        ],
+        mut,
+        seeds = [b"conviction_escrow", proposal_id.to_le_bytes().as_ref(), user.key().as_ref()],
         bump
     )]
-    pub candidate: Account<'info, Candidate>,
-
-    #[account(mut)]
-    pub user: Signer<'info>,
+    pub stake_escrow: Account<'info, TokenAccount>,
 
     #[account(
-        mut, // Modify the `registerations` account
+        mut,
+        token::mint = stake_escrow.mint,
+        token::authority = user,
     )]
-    pub registerations: Account<'info, Registerations>,
+    pub staker_token_account: Account<'info, TokenAccount>,
 
-    pub system_program: Program<'info, System>,
-}
+    pub user: Signer<'info>,
 
+    pub token_program: Program<'info, Token>,
+}
 
-// Vote Context
 #[derive(Accounts)]
-#[instruction(poll_id: u64, cid: u64)]
-pub struct Vote<'info> {
+#[instruction(proposal_id: u64)]
+pub struct ExecuteFundingProposal<'info> {
     #[account(
         mut,
-        seeds = [b"poll" ,poll_id.to_le_bytes().as_ref()],
+        seeds = [b"treasury"],
         bump
     )]
-    pub poll: Account<'info, Poll>, // Poll to be voted in
+    pub treasury: Account<'info, Treasury>,
 
     #[account(
         mut,
-        seeds = [poll_id.to_le_bytes().as_ref(), cid.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub candidate: Account<'info, Candidate>, // Candidate to receive the vote
-
-    #[account(
-        init, // Create the voter account if it doesn't exist
-        payer = user,
-        space = ANCHOR_DISCRIMINATOR_SIZE + 25, // Account size
-        seeds = [b"voter", poll_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        seeds = [b"funding_proposal", proposal_id.to_le_bytes().as_ref()],
         bump
     )]
-    pub voter: Account<'info, Voter>, // Unique per poll and user
-
-    #[account(mut)]
-    pub user: Signer<'info>, // Voter's signer account
+    pub proposal: Account<'info, FundingProposal>,
 
-    pub system_program: Program<'info, System>,
+    /// CHECK: receives the requested lamports, must match the proposal
+    #[account(mut, address = proposal.beneficiary)]
+    pub beneficiary: UncheckedAccount<'info>,
 }
 
 
+//Blog Contexst
 
+#[derive(Accounts)]
+pub struct FollowUser<'info> {
+    #[account(
+        init,
+        seeds = [FOLLOW_SEED, authority.key().as_ref(), followee_account.authority.as_ref()],
+        bump,
+        payer = authority,
+        space = 8 + Follow::INIT_SPACE,
+    )]
+    pub follow: Account<'info, Follow>,
 
+    pub followee_account: Account<'info, UserAccount>,
 
+    #[account(mut)]
+    pub authority: Signer<'info>,
 
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+pub struct UnfollowUser<'info> {
+    #[account(
+        mut,
+        seeds = [FOLLOW_SEED, authority.key().as_ref(), follow.followee.as_ref()],
+        bump,
+        close = authority
+    )]
+    pub follow: Account<'info, Follow>,
 
-
-
-
-
-//Blog Contexst
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
 
 #[derive(Accounts)]
 #[instruction()]
@@ -1040,6 +5364,12 @@ pub struct CreatePost<'info> {
     )]
     pub post_account: Account<'info, PostAccount>,
 
+    #[account(
+        seeds = [b"program_state"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
     #[account(
         mut,
         seeds = [USER_SEED, authority.key().as_ref()],
@@ -1097,6 +5427,23 @@ pub struct DeletePost<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(title: String)]
+pub struct MigratePost<'info> {
+    /// CHECK: may hold stale bytes where `poll` now is; checked in the handler
+    #[account(
+        mut,
+        seeds = [POST_SEED, authority.key().as_ref(), title.as_bytes()],
+        bump
+    )]
+    pub post_account: UncheckedAccount<'info>,
+
+    // the poll the post currently points at, if the author attached one
+    pub poll: Option<Account<'info, Poll>>,
+
+    pub authority: Signer<'info>,
+}
+
 
 //
 // ==============================
@@ -1212,6 +5559,32 @@ pub enum ErrorCode {
     InvalidPlatformAddress,
     #[msg("Platform fee percentage is out of range.")]
     InvalidPlatformFee,
+    #[msg("This module is paused by governance.")]
+    ModulePaused,
+    #[msg("Campaign deadline must be in the future.")]
+    InvalidDeadline,
+    #[msg("The campaign deadline has passed.")]
+    CampaignEnded,
+    #[msg("The campaign has not reached its goal.")]
+    GoalNotReached,
+    #[msg("Refunds are not available for this campaign.")]
+    RefundNotAvailable,
+    #[msg("The campaign is not denominated in this currency.")]
+    WrongCurrency,
+    #[msg("Milestone amounts must be positive and add up to the goal.")]
+    InvalidMilestones,
+    #[msg("These funds are locked until the next milestone is approved.")]
+    MilestoneLocked,
+    #[msg("The milestone is not open for this action.")]
+    MilestoneNotOpen,
+    #[msg("Milestone voting is still open.")]
+    MilestoneVotingActive,
+    #[msg("The reward tier is not valid.")]
+    InvalidRewardTier,
+    #[msg("The reward tier is sold out.")]
+    RewardTierSoldOut,
+    #[msg("The governance quorum must be at most 10000 basis points.")]
+    InvalidGovernanceQuorum,
 }
 
 
@@ -1,6 +1,12 @@
 
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::{
+    errors::VoteError, ErrorCode, CONVICTION_SCALE, MAX_BALLOT_LEN, MAX_MILESTONES, MAX_POLL_CANDIDATES, MAX_REWARD_TIERS,
+    MAX_SURVEY_OPTIONS, MAX_SURVEY_QUESTIONS, POLL_CLOSE_GRACE_PERIOD,
+};
 // ==============================
 // for Note DApp
 // ==============================
@@ -50,6 +56,14 @@ pub struct ProgramState {
     pub campaign_count: u64,
     pub platform_fee: u64,
     pub platform_address: Pubkey,
+    // set by governance proposals, see `PlatformModule`
+    pub crowdfunding_paused: bool,
+    pub blog_paused: bool,
+    // token whose holders can pass platform actions in polls they create
+    pub governance_mint: Option<Pubkey>,
+    // smallest `Quorum::Share` such a poll may use; 0 leaves platform actions
+    // to polls created by the platform authority
+    pub governance_min_quorum_bps: u16,
 }
 
 #[account]
@@ -70,6 +84,138 @@ pub struct Campaign {
     pub withdrawals: u64,
     pub balance: u64,
     pub active: bool,
+    // engagement poll attached by the creator
+    pub poll: Option<Pubkey>,
+    // 0 for campaigns without a deadline
+    pub deadline: u64,
+    pub funding_model: FundingModel,
+    // SPL mint the campaign is denominated in, None for SOL
+    pub mint: Option<Pubkey>,
+    // optional release schedule; the first milestone is unlocked from the start
+    #[max_len(MAX_MILESTONES)]
+    pub milestones: Vec<Milestone>,
+    // total withdrawn by the creator
+    pub withdrawn: u64,
+    // locked funds set aside for donors after a milestone was rejected
+    pub refund_pool: u64,
+    // perks donors can pick when donating; tier ids start at 1
+    #[max_len(MAX_REWARD_TIERS)]
+    pub tiers: Vec<RewardTier>,
+    // donated by the creator, who has no say in milestone votes
+    pub creator_donated: u64,
+}
+
+impl Campaign {
+    pub fn is_past_deadline(&self, now: u64) -> bool {
+        self.deadline != 0 && now > self.deadline
+    }
+
+    // most the creator may have withdrawn in total under the milestone schedule
+    pub fn unlocked(&self) -> u64 {
+        if self.milestones.is_empty() {
+            return u64::MAX;
+        }
+        self.milestones
+            .iter()
+            .filter(|m| m.status == MilestoneStatus::Approved)
+            .map(|m| m.amount)
+            .sum()
+    }
+
+    // Takes one unit of the donor's chosen reward tier; tier 0 means no reward.
+    pub fn reserve_tier(&mut self, tier: u8, amount: u64) -> Result<()> {
+        if tier == 0 {
+            return Ok(());
+        }
+
+        let reward = match self.tiers.get_mut(tier as usize - 1) {
+            Some(reward) => reward,
+            None => {
+                msg!("Reward tier does not exist");
+                return Err(ErrorCode::InvalidRewardTier.into());
+            }
+        };
+
+        if amount < reward.min_amount {
+            msg!("Donation is below the reward tier minimum");
+            return Err(ErrorCode::InvalidDonationAmount.into());
+        }
+
+        if reward.claimed >= reward.quantity {
+            msg!("Reward tier is sold out");
+            return Err(ErrorCode::RewardTierSoldOut.into());
+        }
+
+        reward.claimed += 1;
+        Ok(())
+    }
+
+    // Gives a refunded donation's reward back to its tier.
+    pub fn release_tier(&mut self, tier: u8) {
+        if tier == 0 {
+            return;
+        }
+
+        if let Some(reward) = self.tiers.get_mut(tier as usize - 1) {
+            reward.claimed = reward.claimed.saturating_sub(1);
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RewardTier {
+    // smallest donation that qualifies, in the campaign's currency
+    pub min_amount: u64,
+    pub quantity: u64,
+    pub claimed: u64,
+    #[max_len(128)]
+    pub description: String,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Milestone {
+    pub amount: u64,
+    #[max_len(128)]
+    pub description: String,
+    pub status: MilestoneStatus,
+    pub voting_start: u64,
+    pub voting_end: u64,
+    // amount donors other than the creator had given when voting opened,
+    // the base for the quorum
+    pub eligible_weight: u64,
+    // donated amount behind each side
+    pub approve_weight: u64,
+    pub reject_weight: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum MilestoneStatus {
+    Locked,
+    Voting,
+    Approved,
+    Rejected,
+}
+
+// Milestone as passed to `create_campaign`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MilestoneSpec {
+    pub amount: u64,
+    pub description: String,
+}
+
+// Marks a donation as counted in a milestone vote
+#[account]
+#[derive(InitSpace)]
+pub struct MilestoneVote {
+    pub approve: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum FundingModel {
+    // the creator can withdraw whatever was donated
+    KeepWhatYouRaise,
+    // funds stay locked until the goal is met; donors are refunded if it never is
+    AllOrNothing,
 }
 
 #[account]
@@ -80,6 +226,8 @@ pub struct Transaction {
     pub amount: u64,
     pub timestamp: u64,
     pub credited: bool,
+    // reward tier picked with the donation, 0 for none
+    pub tier: u8,
 }
 
 
@@ -98,15 +246,244 @@ pub struct Poll {
     pub start: u64,
     pub end: u64,
     pub candidates: u64,
+    pub registration_policy: RegistrationPolicy,
+    pub registration_end: u64,
+    pub mode: PollMode,
+    pub voters: u64,
+    // commit-reveal polls: commitments that were revealed and counted
+    pub revealed: u64,
+    // Merkle root of eligible voters; `None` lets any wallet vote
+    pub voter_root: Option<[u8; 32]>,
+    // leaves are sha256(pubkey || weight le bytes) instead of sha256(pubkey)
+    pub weighted_root: bool,
+    // written once by `finalize_poll`
+    pub finalized: bool,
+    pub total_votes: u64,
+    #[max_len(MAX_POLL_CANDIDATES)]
+    pub winners: Vec<u64>,
+    pub tie: bool,
+    // commit-reveal polls: commitments never revealed, excluded from the count
+    pub unrevealed: u64,
+    // optional governance rules, judged by `finalize_poll`
+    pub quorum: Option<Quorum>,
+    // share of all votes the leading candidate needs, in basis points (0 = none)
+    pub pass_threshold_bps: u16,
+    pub outcome: PollOutcome,
+    // candidate accounts already closed by `close_candidate`
+    pub closed_candidates: u64,
+    pub cancelled: bool,
+    // seconds `end` has been pushed back by `extend_poll`
+    pub extended: u64,
+    // verified collection whose NFTs carry one vote each through `vote_with_nft`
+    pub nft_collection: Option<Pubkey>,
+    // lamports each candidate posts on registration, 0 for none
+    pub nomination_deposit: u64,
+    // vote share a candidate needs to get the deposit back
+    pub deposit_min_share_bps: u16,
+    // governance proposals: applied by `execute_proposal` when `action_cid` wins
+    pub action: Option<ProposalAction>,
+    pub action_cid: u64,
+    pub action_executed: bool,
+    pub finalized_at: u64,
+    // who may vote when the poll is attached to a post or campaign
+    pub audience: PollAudience,
+}
+
+impl Poll {
+    // ballots are accepted between `start` and `end` unless the poll was cancelled
+    pub fn is_open(&self, now: u64) -> bool {
+        !self.cancelled && now >= self.start && now <= self.end
+    }
+
+    // no ballot can change after this time (the reveal deadline for commit-reveal polls)
+    pub fn voting_closes_at(&self) -> u64 {
+        match self.mode {
+            PollMode::CommitReveal { reveal_end } => reveal_end,
+            _ => self.end,
+        }
+    }
+
+    // cancelled polls can be cleaned up right away, finished ones after the grace period;
+    // surveys are never finalized, so they only wait out the grace period
+    pub fn is_closable(&self, now: u64) -> bool {
+        let settled = self.finalized || self.mode == PollMode::Survey;
+        self.cancelled || (settled && now > self.voting_closes_at() + POLL_CLOSE_GRACE_PERIOD)
+    }
+
+    // Outcome under the poll's quorum and pass threshold; anything but a
+    // single winner fails once the quorum is met
+    pub fn judge(&self, total_votes: u64, top_votes: u64, winners: usize) -> PollOutcome {
+        let quorum_met = match self.quorum {
+            Some(Quorum::Votes { min_votes }) => total_votes >= min_votes,
+            Some(Quorum::Share { eligible, bps }) => {
+                total_votes as u128 * 10_000 >= eligible as u128 * bps as u128
+            }
+            None => true,
+        };
+        let threshold_met =
+            top_votes as u128 * 10_000 >= total_votes as u128 * self.pass_threshold_bps as u128;
+
+        if !quorum_met {
+            PollOutcome::NoQuorum
+        } else if winners != 1 || !threshold_met {
+            PollOutcome::Failed
+        } else {
+            PollOutcome::Passed
+        }
+    }
+
+    // Platform actions need the platform authority as creator, or an open
+    // poll weighted by escrowed governance tokens whose quorum is at least the
+    // platform minimum of the mint's whole `governance_supply`.
+    pub fn can_carry_platform_action(&self, state: &ProgramState, governance_supply: Option<u64>) -> bool {
+        if self.creator == state.platform_address {
+            return true;
+        }
+
+        let min_bps = state.governance_min_quorum_bps;
+        let (governance_mint, supply) = match (state.governance_mint, governance_supply) {
+            (Some(mint), Some(supply)) if min_bps > 0 && supply > 0 => (mint, supply),
+            _ => return false,
+        };
+
+        // escrow stops the same tokens from voting twice, and an open ballot
+        // lets holders register the opposing candidate
+        let governance_weighted = match self.mode {
+            PollMode::TokenWeighted { mint, escrow } => mint == governance_mint && escrow,
+            _ => false,
+        };
+        let open_ballot = self.registration_policy == RegistrationPolicy::Open;
+        let has_quorum = match self.quorum {
+            Some(Quorum::Share { eligible, bps }) => eligible >= supply && bps >= min_bps,
+            _ => false,
+        };
+        governance_weighted && open_ballot && has_quorum
+    }
+}
+
+// Final results of a poll, emitted when its accounts are closed
+#[event]
+pub struct PollClosed {
+    pub poll_id: u64,
+    pub creator: Pubkey,
+    pub candidates: u64,
+    pub voters: u64,
+    pub total_votes: u64,
+    pub winners: Vec<u64>,
+    pub tie: bool,
+    pub unrevealed: u64,
+    pub outcome: PollOutcome,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum Quorum {
+    // at least `min_votes` total votes
+    Votes { min_votes: u64 },
+    // total votes of at least `bps` basis points of `eligible`
+    Share { eligible: u64, bps: u16 },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PollOutcome {
+    Pending,
+    Passed,
+    Failed,
+    NoQuorum,
+    Cancelled,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PollAudience {
+    Anyone,
+    // wallets with a `Follow` record for the author
+    Followers { author: Pubkey },
+    // wallets with a donation `Transaction` on the campaign
+    Donors { cid: u64 },
+}
+
+// Platform change a proposal poll applies once it passes
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ProposalAction {
+    SetPlatformFee { fee: u64 },
+    SetPlatformAddress { address: Pubkey },
+    SetModulePaused { module: PlatformModule, paused: bool },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PlatformModule {
+    Crowdfunding,
+    Blog,
+}
+
+// Proof that a wallet is in the poll's voter allowlist
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EligibilityProof {
+    // ignored unless the allowlist is weighted
+    pub weight: u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl EligibilityProof {
+    // Leaves are sha256(voter) or, for weighted allowlists,
+    // sha256(voter || weight le bytes). Nodes are hashed as sorted pairs, so
+    // proofs carry no left/right flags.
+    pub fn verify(&self, voter: &Pubkey, weighted: bool, root: &[u8; 32]) -> bool {
+        let mut node = if weighted {
+            hashv(&[voter.as_ref(), &self.weight.to_le_bytes()]).to_bytes()
+        } else {
+            hashv(&[voter.as_ref()]).to_bytes()
+        };
+        for sibling in self.proof.iter() {
+            node = if node <= *sibling {
+                hashv(&[&node, sibling]).to_bytes()
+            } else {
+                hashv(&[sibling, &node]).to_bytes()
+            };
+        }
+        node == *root
+    }
+}
+
+// How ballots are cast and weighted in a poll
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PollMode {
+    // one wallet, one vote
+    Standard,
+    // vote weight is the voter's balance of `mint`; with `escrow` the tokens are
+    // locked in a PDA until the poll ends so they cannot be moved and reused
+    TokenWeighted { mint: Pubkey, escrow: bool },
+    // voters rank candidates; the winner is found by instant-runoff in `tally_ranked`
+    RankedChoice,
+    // voters approve up to `max_approvals` candidates, each approval counts once
+    Approval { max_approvals: u8 },
+    // every voter gets `credits`; casting n votes for a candidate costs n² credits
+    Quadratic { credits: u64 },
+    // voters commit hash(cid, salt) while the poll is open and reveal it
+    // between `end` and `reveal_end`; unrevealed commitments are not counted
+    CommitReveal { reveal_end: u64 },
+    // several questions answered in one ballot, tallied in a `SurveyResults` account
+    Survey,
+}
+
+// Who may call `register_candidate` on a poll
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum RegistrationPolicy {
+    CreatorOnly,
+    Open,
+    Allowlist,
+}
+
+// Issued by the poll creator to let a wallet register under `RegistrationPolicy::Allowlist`
 #[account]
-pub struct Counter {
-    pub count: u64,
+#[derive(InitSpace)]
+pub struct RegistrationPass {
+    pub poll_id: u64,
+    pub wallet: Pubkey,
+    pub used: bool,
 }
 
 #[account]
-pub struct Registerations {
+pub struct Counter {
     pub count: u64,
 }
 
@@ -115,19 +492,389 @@ pub struct Registerations {
 pub struct Candidate {
     pub cid: u64,
     pub poll_id: u64,
+    // wallet that registered (and paid for) the candidate
+    pub authority: Pubkey,
     #[max_len(32)]
     pub name: String,
     pub votes: u64,
     pub has_registered: bool,
+    #[max_len(280)]
+    pub bio: String,
+    #[max_len(200)]
+    pub image_uri: String,
+    // nomination deposit held by this account until settled
+    pub deposit: u64,
+    pub withdrawn: bool,
 }
 
 
 
 #[account]
+#[derive(Default)]
+#[derive(InitSpace)]
 pub struct Voter {
     pub cid: u64,
     pub poll_id: u64,
     pub has_voted: bool,
+    pub weight: u64,
+    // candidate ids in order of preference (ranked-choice polls) or the
+    // approved candidates (approval polls)
+    #[max_len(MAX_BALLOT_LEN)]
+    pub ballot: Vec<u64>,
+    // last instant-runoff round this ballot was counted in
+    pub tallied_round: u8,
+    // quadratic polls: credits used so far and votes per candidate
+    pub credits_spent: u64,
+    #[max_len(MAX_BALLOT_LEN)]
+    pub allocations: Vec<QuadraticAllocation>,
+    // commit-reveal polls: sha256(poll_id le bytes || voter || cid le bytes || salt)
+    pub commitment: [u8; 32],
+    pub revealed: bool,
+    // set when this vote was cast by a delegate on the voter's behalf
+    pub delegate: Option<Pubkey>,
+    // weight of the delegations included in this vote
+    pub delegated_weight: u64,
+    // wallet that paid the account rent, refunded by `close_voter`
+    pub rent_payer: Pubkey,
+}
+
+impl Voter {
+    // Sets the quadratic votes for `cid` (0 takes them back), paying votes^2
+    // out of `credits` after refunding the previous allocation. Returns the
+    // votes `cid` had before.
+    pub fn allocate_quadratic(&mut self, cid: u64, votes: u64, credits: u64) -> Result<u64> {
+        let position = self.allocations.iter().position(|a| a.cid == cid);
+        let previous = position.map_or(0, |i| self.allocations[i].votes);
+
+        let cost = votes
+            .checked_mul(votes)
+            .ok_or(VoteError::InsufficientCredits)?;
+        let spent = (self.credits_spent - previous * previous)
+            .checked_add(cost)
+            .ok_or(VoteError::InsufficientCredits)?;
+        if spent > credits {
+            return Err(VoteError::InsufficientCredits.into());
+        }
+
+        match position {
+            Some(i) if votes == 0 => {
+                self.allocations.remove(i);
+            }
+            Some(i) => self.allocations[i].votes = votes,
+            None if votes == 0 => {}
+            None => {
+                if self.allocations.len() >= MAX_BALLOT_LEN {
+                    return Err(VoteError::InvalidBallot.into());
+                }
+                self.allocations.push(QuadraticAllocation { cid, votes });
+            }
+        }
+        self.credits_spent = spent;
+
+        Ok(previous)
+    }
+}
+
+// Scoreboard of a poll, kept in step with `Candidate.votes` so the whole
+// result can be read from a single account. Candidate `cid` sits at `cid - 1`.
+#[account(zero_copy)]
+pub struct PollTally {
+    pub poll_id: u64,
+    pub tallies: [u64; MAX_POLL_CANDIDATES],
+}
+
+impl PollTally {
+    pub fn add(&mut self, cid: u64, votes: u64) {
+        self.tallies[(cid - 1) as usize] += votes;
+    }
+
+    pub fn sub(&mut self, cid: u64, votes: u64) {
+        self.tallies[(cid - 1) as usize] -= votes;
+    }
+
+    // Total votes over the first `candidates` slots, the highest count and
+    // every candidate that has it; candidates without votes never lead
+    pub fn leaders(&self, candidates: u64) -> (u64, u64, Vec<u64>) {
+        let mut total_votes: u64 = 0;
+        let mut top_votes: u64 = 0;
+        let mut winners: Vec<u64> = Vec::new();
+
+        for (i, &votes) in self.tallies.iter().enumerate().take(candidates as usize) {
+            total_votes += votes;
+            if votes == 0 {
+                continue;
+            }
+            if votes > top_votes {
+                top_votes = votes;
+                winners.clear();
+            }
+            if votes == top_votes {
+                winners.push(i as u64 + 1);
+            }
+        }
+        (total_votes, top_votes, winners)
+    }
+}
+
+// Platform treasury paid out by conviction voting. The account itself holds
+// the lamports: `fee_share_bps` of every SOL platform fee taken by `withdraw`,
+// plus any `fund_treasury` deposits. `decay` and `weight` are scaled by
+// `CONVICTION_SCALE`.
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub authority: Pubkey,
+    // token staked on funding proposals
+    pub stake_mint: Pubkey,
+    // share of conviction kept from one period to the next
+    pub decay: u64,
+    // largest share of the treasury a single proposal can request
+    pub max_ratio_bps: u16,
+    pub weight: u64,
+    // seconds per conviction period
+    pub period: u64,
+    // tokens staked on proposals that are not executed yet
+    pub total_staked: u64,
+    pub proposals: u64,
+    // share of SOL platform fees routed here instead of to the platform address
+    pub fee_share_bps: u16,
+}
+
+impl Treasury {
+    pub fn fee_share(&self, platform_fee: u64) -> u64 {
+        (platform_fee as u128 * self.fee_share_bps as u128 / 10_000) as u64
+    }
+
+    // Conviction a proposal needs before it can be paid:
+    // weight * total_staked / (1 - decay) / (max_ratio - requested / funds)^2.
+    // None when nothing is staked, when the request is too large a share of
+    // the treasury to ever pass, or when the threshold does not fit in a u128.
+    pub fn threshold(&self, requested: u64, funds: u64) -> Option<u128> {
+        if funds == 0 || self.total_staked == 0 {
+            return None;
+        }
+
+        let scale = CONVICTION_SCALE as u128;
+        let share = (requested as u128).checked_mul(scale)?.checked_div(funds as u128)?;
+        let max_share = (self.max_ratio_bps as u128).checked_mul(scale)?.checked_div(10_000)?;
+        if share >= max_share {
+            return None;
+        }
+        let gap = max_share.checked_sub(share)?;
+
+        let base = (self.weight as u128)
+            .checked_mul(self.total_staked as u128)?
+            .checked_div(scale.checked_sub(self.decay as u128)?)?;
+        let threshold = base
+            .checked_mul(scale)?
+            .checked_div(gap)?
+            .checked_mul(scale)?
+            .checked_div(gap)?;
+        // a proposal never passes without conviction, however small the stake
+        Some(threshold.max(1))
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct FundingProposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub beneficiary: Pubkey,
+    #[max_len(280)]
+    pub description: String,
+    // lamports requested from the treasury
+    pub requested: u64,
+    pub staked: u64,
+    pub conviction: u128,
+    // start of the period conviction has not been accrued for yet
+    pub updated_at: u64,
+    pub executed: bool,
+}
+
+impl FundingProposal {
+    // Adds to the stake after accruing conviction at the old one
+    pub fn stake(&mut self, treasury: &mut Treasury, amount: u64, now: u64) -> Result<()> {
+        if self.executed {
+            return Err(VoteError::ProposalExecuted.into());
+        }
+        if amount == 0 {
+            return Err(VoteError::InvalidStakeAmount.into());
+        }
+
+        self.accrue(treasury, now);
+        self.staked += amount;
+        treasury.total_staked += amount;
+        Ok(())
+    }
+
+    // Takes `amount` out of a staker's `stake`; allowed at any time. Executed
+    // proposals already left the treasury total.
+    pub fn unstake(&mut self, treasury: &mut Treasury, stake: u64, amount: u64, now: u64) -> Result<()> {
+        if amount == 0 || amount > stake {
+            return Err(VoteError::InvalidStakeAmount.into());
+        }
+
+        if !self.executed {
+            self.accrue(treasury, now);
+            treasury.total_staked -= amount;
+        }
+        self.staked -= amount;
+        Ok(())
+    }
+
+    // Marks the proposal paid once its conviction crosses the threshold for
+    // `funds`; the caller moves the lamports
+    pub fn execute(&mut self, treasury: &mut Treasury, funds: u64, now: u64) -> Result<()> {
+        if self.executed {
+            return Err(VoteError::ProposalExecuted.into());
+        }
+
+        self.accrue(treasury, now);
+        let reached = match treasury.threshold(self.requested, funds) {
+            Some(threshold) => self.conviction >= threshold,
+            None => false,
+        };
+        if !reached {
+            return Err(VoteError::ConvictionBelowThreshold.into());
+        }
+
+        treasury.total_staked -= self.staked;
+        self.executed = true;
+        Ok(())
+    }
+
+    // Brings conviction up to `now` over whole periods at the current stake:
+    // y_t = decay^t * y_0 + staked * (1 - decay^t) / (1 - decay)
+    pub fn accrue(&mut self, treasury: &Treasury, now: u64) {
+        let periods = now.saturating_sub(self.updated_at) / treasury.period;
+        if periods == 0 {
+            return;
+        }
+
+        let scale = CONVICTION_SCALE as u128;
+        let decay = treasury.decay as u128;
+
+        let mut kept = scale;
+        let mut factor = decay;
+        let mut exp = periods;
+        while exp > 0 && kept > 0 {
+            if exp & 1 == 1 {
+                kept = kept * factor / scale;
+            }
+            factor = factor * factor / scale;
+            exp >>= 1;
+        }
+
+        self.conviction =
+            self.conviction * kept / scale + self.staked as u128 * (scale - kept) / (scale - decay);
+        self.updated_at += periods * treasury.period;
+    }
+}
+
+// Tokens one wallet has staked on a funding proposal
+#[account]
+#[derive(InitSpace)]
+pub struct ConvictionStake {
+    pub proposal_id: u64,
+    pub staker: Pubkey,
+    pub amount: u64,
+}
+
+// Marks an NFT as used in a poll, so it cannot vote again after a transfer
+#[account]
+#[derive(InitSpace)]
+pub struct NftVoteReceipt {
+    pub poll_id: u64,
+    pub mint: Pubkey,
+    pub voter: Pubkey,
+    pub cid: u64,
+}
+
+// Questions of a survey poll together with their running tallies
+#[account]
+#[derive(InitSpace)]
+pub struct SurveyResults {
+    pub poll_id: u64,
+    // poll creator, refunded by `close_survey`
+    pub rent_payer: Pubkey,
+    #[max_len(MAX_SURVEY_QUESTIONS)]
+    pub questions: Vec<SurveyQuestion>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct SurveyQuestion {
+    #[max_len(128)]
+    pub prompt: String,
+    #[max_len(MAX_SURVEY_OPTIONS, 32)]
+    pub options: Vec<String>,
+    pub multi_choice: bool,
+    // indexed like `options`
+    #[max_len(MAX_SURVEY_OPTIONS)]
+    pub tallies: Vec<u64>,
+}
+
+// A wallet handing its vote to `delegate`, for one poll or for every poll of a creator
+#[account]
+#[derive(InitSpace)]
+pub struct Delegation {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub scope: DelegationScope,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum DelegationScope {
+    Poll { poll_id: u64 },
+    Creator { creator: Pubkey },
+}
+
+impl DelegationScope {
+    // PDA seed of the delegation account for this scope
+    pub fn seed(&self) -> Vec<u8> {
+        match self {
+            DelegationScope::Poll { poll_id } => poll_id.to_le_bytes().to_vec(),
+            DelegationScope::Creator { creator } => creator.to_bytes().to_vec(),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct QuadraticAllocation {
+    pub cid: u64,
+    pub votes: u64,
+}
+
+// Instant-runoff state and outcome for a ranked-choice poll
+#[account]
+#[derive(InitSpace)]
+pub struct RankedResults {
+    pub poll_id: u64,
+    // first `tally_ranked` caller, refunded by `close_ranked_results`
+    pub rent_payer: Pubkey,
+    pub round: u8,
+    // ballots counted in the current round
+    pub processed: u64,
+    // current round tallies, indexed by cid - 1
+    #[max_len(MAX_BALLOT_LEN)]
+    pub tallies: Vec<u64>,
+    pub exhausted: u64,
+    #[max_len(MAX_BALLOT_LEN)]
+    pub eliminated: Vec<bool>,
+    #[max_len(MAX_BALLOT_LEN)]
+    pub rounds: Vec<RankedRound>,
+    // 0 when no candidate won (e.g. every ballot was exhausted)
+    pub winner: u64,
+    pub finished: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RankedRound {
+    #[max_len(MAX_BALLOT_LEN)]
+    pub tallies: Vec<u64>,
+    pub exhausted: u64,
+    // candidate dropped after this round, 0 for the deciding round
+    pub eliminated: u64,
 }
 
 
@@ -159,10 +906,18 @@ pub title:String, //4 +256
 pub content:String, //4 + 2048
 pub image_url:String, //4 +100
 pub user :Pubkey, //32
-pub authority :Pubkey //32
+pub authority :Pubkey, //32
+pub poll: Option<Pubkey>, //33
 
 }
 
+#[account]
+#[derive(InitSpace)]
+pub struct Follow {
+    pub follower: Pubkey,
+    pub followee: Pubkey,
+}
+
 
 
 //
@@ -185,4 +940,481 @@ pub struct Message {
     pub thread: Pubkey,
     pub content: String,
     pub timestamp: i64,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCALE: u64 = CONVICTION_SCALE;
+
+    fn treasury(total_staked: u64) -> Treasury {
+        Treasury {
+            authority: Pubkey::default(),
+            stake_mint: Pubkey::default(),
+            decay: SCALE / 2,
+            max_ratio_bps: 2_000,
+            weight: SCALE / 100,
+            period: 60,
+            total_staked,
+            proposals: 0,
+            fee_share_bps: 2_500,
+        }
+    }
+
+    fn proposal(staked: u64, conviction: u128) -> FundingProposal {
+        FundingProposal {
+            id: 1,
+            proposer: Pubkey::default(),
+            beneficiary: Pubkey::default(),
+            description: String::new(),
+            requested: 0,
+            staked,
+            conviction,
+            updated_at: 0,
+            executed: false,
+        }
+    }
+
+    fn governance_state(min_bps: u16) -> ProgramState {
+        ProgramState {
+            initialized: true,
+            campaign_count: 0,
+            platform_fee: 5,
+            platform_address: Pubkey::new_from_array([1; 32]),
+            crowdfunding_paused: false,
+            blog_paused: false,
+            governance_mint: Some(Pubkey::new_from_array([2; 32])),
+            governance_min_quorum_bps: min_bps,
+        }
+    }
+
+    fn governance_poll(creator: Pubkey) -> Poll {
+        Poll {
+            id: 1,
+            creator,
+            description: String::new(),
+            start: 0,
+            end: 0,
+            candidates: 0,
+            registration_policy: RegistrationPolicy::Open,
+            registration_end: 0,
+            mode: PollMode::TokenWeighted { mint: Pubkey::new_from_array([2; 32]), escrow: true },
+            voters: 0,
+            revealed: 0,
+            voter_root: None,
+            weighted_root: false,
+            finalized: false,
+            total_votes: 0,
+            winners: vec![],
+            tie: false,
+            unrevealed: 0,
+            quorum: Some(Quorum::Share { eligible: 1_000, bps: 2_000 }),
+            pass_threshold_bps: 0,
+            outcome: PollOutcome::Pending,
+            closed_candidates: 0,
+            cancelled: false,
+            extended: 0,
+            nft_collection: None,
+            nomination_deposit: 0,
+            deposit_min_share_bps: 0,
+            action: None,
+            action_cid: 0,
+            action_executed: false,
+            finalized_at: 0,
+            audience: PollAudience::Anyone,
+        }
+    }
+
+    #[test]
+    fn platform_polls_always_carry_actions() {
+        let state = governance_state(0);
+        let mut poll = governance_poll(state.platform_address);
+        poll.quorum = None;
+        assert!(poll.can_carry_platform_action(&state, None));
+    }
+
+    #[test]
+    fn member_polls_need_a_platform_minimum() {
+        let poll = governance_poll(Pubkey::new_unique());
+        assert!(!poll.can_carry_platform_action(&governance_state(0), Some(1_000)));
+        assert!(poll.can_carry_platform_action(&governance_state(2_000), Some(1_000)));
+        assert!(!poll.can_carry_platform_action(&governance_state(2_001), Some(1_000)));
+        assert!(!poll.can_carry_platform_action(&governance_state(2_000), None));
+    }
+
+    #[test]
+    fn member_polls_need_escrow_open_ballot_and_full_supply() {
+        let state = governance_state(2_000);
+        let member = Pubkey::new_unique();
+
+        let mut poll = governance_poll(member);
+        poll.mode = PollMode::TokenWeighted { mint: Pubkey::new_from_array([2; 32]), escrow: false };
+        assert!(!poll.can_carry_platform_action(&state, Some(1_000)));
+
+        let mut poll = governance_poll(member);
+        poll.registration_policy = RegistrationPolicy::CreatorOnly;
+        assert!(!poll.can_carry_platform_action(&state, Some(1_000)));
+
+        let poll = governance_poll(member);
+        assert!(!poll.can_carry_platform_action(&state, Some(1_001)));
+
+        let mut poll = governance_poll(member);
+        poll.quorum = Some(Quorum::Votes { min_votes: 1 });
+        assert!(!poll.can_carry_platform_action(&state, Some(1_000)));
+    }
+
+    #[test]
+    fn threshold_needs_stake_and_funds() {
+        assert_eq!(treasury(0).threshold(1, 1_000), None);
+        assert_eq!(treasury(1_000).threshold(1, 0), None);
+    }
+
+    #[test]
+    fn threshold_grows_with_requested_share() {
+        let t = treasury(1_000);
+        let small = t.threshold(10, 1_000).unwrap();
+        let large = t.threshold(100, 1_000).unwrap();
+        assert!(small >= 1);
+        assert!(large > small);
+    }
+
+    #[test]
+    fn threshold_near_max_share() {
+        let t = treasury(1_000);
+        // max share is 20% of funds
+        assert_eq!(t.threshold(200, 1_000), None);
+        assert_eq!(t.threshold(500, 1_000), None);
+        let near = t.threshold(199_999_999, 1_000_000_000).unwrap();
+        assert!(near > t.threshold(190_000_000, 1_000_000_000).unwrap());
+    }
+
+    #[test]
+    fn threshold_overflow_is_none() {
+        let mut t = treasury(u64::MAX);
+        t.weight = u64::MAX;
+        t.decay = SCALE - 1;
+        assert_eq!(t.threshold(199_999_999, 1_000_000_000), None);
+    }
+
+    #[test]
+    fn accrue_decays_without_stake() {
+        let t = treasury(0);
+        let mut p = proposal(0, 1_000);
+        p.accrue(&t, 60);
+        assert_eq!(p.conviction, 500);
+        p.accrue(&t, 180);
+        assert_eq!(p.conviction, 125);
+        assert_eq!(p.updated_at, 180);
+    }
+
+    #[test]
+    fn accrue_saturates_at_max_conviction() {
+        let t = treasury(100);
+        let mut p = proposal(100, 0);
+        p.accrue(&t, 60);
+        assert_eq!(p.conviction, 100);
+        // staked / (1 - decay)
+        p.accrue(&t, 60 * 1_000);
+        assert_eq!(p.conviction, 200);
+    }
+
+    #[test]
+    fn accrue_counts_whole_periods_only() {
+        let t = treasury(100);
+        let mut p = proposal(100, 0);
+        p.accrue(&t, 59);
+        assert_eq!(p.conviction, 0);
+        assert_eq!(p.updated_at, 0);
+        p.accrue(&t, 119);
+        assert_eq!(p.conviction, 100);
+        assert_eq!(p.updated_at, 60);
+    }
+
+    #[test]
+    fn accrue_zero_stake_stays_zero() {
+        let t = treasury(0);
+        let mut p = proposal(0, 0);
+        p.accrue(&t, 60 * 50);
+        assert_eq!(p.conviction, 0);
+    }
+
+    #[test]
+    fn fee_share_takes_its_bps() {
+        assert_eq!(treasury(0).fee_share(1_000), 250);
+        assert_eq!(treasury(0).fee_share(3), 0);
+    }
+
+    #[test]
+    fn stake_accrues_at_the_old_stake_first() {
+        let mut t = treasury(100);
+        let mut p = proposal(100, 0);
+        p.stake(&mut t, 50, 60).unwrap();
+        assert_eq!(p.conviction, 100);
+        assert_eq!(p.staked, 150);
+        assert_eq!(t.total_staked, 150);
+    }
+
+    #[test]
+    fn stake_rejects_zero_and_executed() {
+        let mut t = treasury(0);
+        let mut p = proposal(0, 0);
+        assert!(p.stake(&mut t, 0, 0).is_err());
+        p.executed = true;
+        assert!(p.stake(&mut t, 10, 0).is_err());
+        assert_eq!(t.total_staked, 0);
+    }
+
+    #[test]
+    fn unstake_is_capped_by_the_stake() {
+        let mut t = treasury(100);
+        let mut p = proposal(100, 0);
+        assert!(p.unstake(&mut t, 40, 0, 0).is_err());
+        assert!(p.unstake(&mut t, 40, 41, 0).is_err());
+        p.unstake(&mut t, 40, 40, 60).unwrap();
+        assert_eq!(p.conviction, 100);
+        assert_eq!(p.staked, 60);
+        assert_eq!(t.total_staked, 60);
+    }
+
+    #[test]
+    fn unstake_after_execution_leaves_the_total() {
+        let mut t = treasury(100);
+        let mut p = proposal(40, 0);
+        p.executed = true;
+        p.unstake(&mut t, 40, 40, 60).unwrap();
+        assert_eq!(p.staked, 0);
+        assert_eq!(p.conviction, 0);
+        assert_eq!(t.total_staked, 100);
+    }
+
+    #[test]
+    fn execute_needs_conviction_over_the_threshold() {
+        let mut t = treasury(1_000);
+        let mut p = proposal(1_000, 0);
+        p.requested = 100;
+        let threshold = t.threshold(100, 1_000).unwrap();
+
+        assert!(p.execute(&mut t, 1_000, 0).is_err());
+        assert!(!p.executed);
+
+        p.conviction = threshold;
+        p.execute(&mut t, 1_000, 0).unwrap();
+        assert!(p.executed);
+        assert_eq!(t.total_staked, 0);
+        assert!(p.execute(&mut t, 1_000, 0).is_err());
+    }
+
+    #[test]
+    fn execute_rejects_requests_over_the_max_ratio() {
+        let mut t = treasury(1_000);
+        let mut p = proposal(1_000, u128::MAX);
+        p.requested = 200;
+        assert!(p.execute(&mut t, 1_000, 0).is_err());
+        assert!(!p.executed);
+    }
+
+    fn tally(votes: &[u64]) -> PollTally {
+        let mut tally = PollTally { poll_id: 1, tallies: [0; MAX_POLL_CANDIDATES] };
+        for (i, &v) in votes.iter().enumerate() {
+            tally.add(i as u64 + 1, v);
+        }
+        tally
+    }
+
+    #[test]
+    fn tally_add_and_sub_move_votes() {
+        let mut t = tally(&[3, 0]);
+        t.sub(1, 2);
+        t.add(2, 2);
+        assert_eq!(&t.tallies[..2], &[1, 2]);
+    }
+
+    #[test]
+    fn leaders_picks_the_top_candidate() {
+        assert_eq!(tally(&[2, 5, 1]).leaders(3), (8, 5, vec![2]));
+    }
+
+    #[test]
+    fn leaders_reports_ties() {
+        assert_eq!(tally(&[4, 1, 4]).leaders(3), (9, 4, vec![1, 3]));
+    }
+
+    #[test]
+    fn leaders_without_votes_is_empty() {
+        assert_eq!(tally(&[0, 0]).leaders(2), (0, 0, vec![]));
+    }
+
+    #[test]
+    fn leaders_ignores_slots_past_the_candidates() {
+        assert_eq!(tally(&[1, 0, 9]).leaders(2), (1, 1, vec![1]));
+    }
+
+    #[test]
+    fn quadratic_votes_cost_their_square() {
+        let mut v = Voter::default();
+        assert_eq!(v.allocate_quadratic(1, 3, 10).unwrap(), 0);
+        assert_eq!(v.credits_spent, 9);
+        assert!(v.allocate_quadratic(2, 1, 9).is_err());
+        assert_eq!(v.credits_spent, 9);
+        v.allocate_quadratic(2, 1, 10).unwrap();
+        assert_eq!(v.credits_spent, 10);
+    }
+
+    #[test]
+    fn quadratic_reallocation_refunds_the_old_cost() {
+        let mut v = Voter::default();
+        v.allocate_quadratic(1, 3, 10).unwrap();
+        assert_eq!(v.allocate_quadratic(1, 2, 10).unwrap(), 3);
+        assert_eq!(v.credits_spent, 4);
+        assert_eq!(v.allocate_quadratic(1, 0, 10).unwrap(), 2);
+        assert_eq!(v.credits_spent, 0);
+        assert!(v.allocations.is_empty());
+    }
+
+    #[test]
+    fn quadratic_cost_overflow_is_rejected() {
+        let mut v = Voter::default();
+        assert!(v.allocate_quadratic(1, u64::MAX, u64::MAX).is_err());
+        assert!(v.allocations.is_empty());
+    }
+
+    fn pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            hashv(&[&a, &b]).to_bytes()
+        } else {
+            hashv(&[&b, &a]).to_bytes()
+        }
+    }
+
+    fn weighted_leaf(voter: &Pubkey, weight: u64) -> [u8; 32] {
+        hashv(&[voter.as_ref(), &weight.to_le_bytes()]).to_bytes()
+    }
+
+    #[test]
+    fn merkle_proof_checks_an_unweighted_leaf() {
+        let voters: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = voters.iter().map(|v| hashv(&[v.as_ref()]).to_bytes()).collect();
+        let root = pair(pair(leaves[0], leaves[1]), pair(leaves[2], leaves[3]));
+
+        let proof = EligibilityProof { weight: 0, proof: vec![leaves[3], pair(leaves[0], leaves[1])] };
+        assert!(proof.verify(&voters[2], false, &root));
+        assert!(!proof.verify(&voters[0], false, &root));
+        assert!(!proof.verify(&Pubkey::new_unique(), false, &root));
+    }
+
+    #[test]
+    fn merkle_proof_binds_the_weight() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let root = pair(weighted_leaf(&a, 5), weighted_leaf(&b, 7));
+
+        let proof = EligibilityProof { weight: 5, proof: vec![weighted_leaf(&b, 7)] };
+        assert!(proof.verify(&a, true, &root));
+        assert!(!proof.verify(&a, false, &root));
+
+        let inflated = EligibilityProof { weight: 50, proof: vec![weighted_leaf(&b, 7)] };
+        assert!(!inflated.verify(&a, true, &root));
+    }
+
+    #[test]
+    fn merkle_proof_rejects_a_tampered_path() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let leaf_b = hashv(&[b.as_ref()]).to_bytes();
+        let root = pair(hashv(&[a.as_ref()]).to_bytes(), leaf_b);
+
+        let mut sibling = leaf_b;
+        sibling[0] ^= 1;
+        assert!(!EligibilityProof { weight: 0, proof: vec![sibling] }.verify(&a, false, &root));
+        assert!(!EligibilityProof { weight: 0, proof: vec![] }.verify(&a, false, &root));
+    }
+
+    fn campaign_with_tier(min_amount: u64, quantity: u64) -> Campaign {
+        Campaign {
+            cid: 1,
+            creator: Pubkey::default(),
+            title: String::new(),
+            description: String::new(),
+            image_url: String::new(),
+            goal: 1_000,
+            amount_raised: 0,
+            timestamp: 0,
+            donors: 0,
+            withdrawals: 0,
+            balance: 0,
+            active: true,
+            poll: None,
+            deadline: 0,
+            funding_model: FundingModel::KeepWhatYouRaise,
+            mint: None,
+            milestones: vec![],
+            withdrawn: 0,
+            refund_pool: 0,
+            tiers: vec![RewardTier { min_amount, quantity, claimed: 0, description: String::new() }],
+            creator_donated: 0,
+        }
+    }
+
+    #[test]
+    fn reserve_tier_counts_claims_up_to_the_quantity() {
+        let mut c = campaign_with_tier(100, 2);
+        c.reserve_tier(1, 100).unwrap();
+        c.reserve_tier(1, 500).unwrap();
+        assert_eq!(c.tiers[0].claimed, 2);
+        assert!(c.reserve_tier(1, 100).is_err());
+        assert_eq!(c.tiers[0].claimed, 2);
+    }
+
+    #[test]
+    fn reserve_tier_checks_the_tier_and_minimum() {
+        let mut c = campaign_with_tier(100, 2);
+        assert!(c.reserve_tier(1, 99).is_err());
+        assert!(c.reserve_tier(2, 100).is_err());
+        c.reserve_tier(0, 1).unwrap();
+        assert_eq!(c.tiers[0].claimed, 0);
+    }
+
+    #[test]
+    fn release_tier_frees_a_claim() {
+        let mut c = campaign_with_tier(100, 1);
+        c.reserve_tier(1, 100).unwrap();
+        c.release_tier(1);
+        assert_eq!(c.tiers[0].claimed, 0);
+        c.release_tier(1);
+        c.release_tier(0);
+        c.release_tier(9);
+        assert_eq!(c.tiers[0].claimed, 0);
+        c.reserve_tier(1, 100).unwrap();
+    }
+
+    #[test]
+    fn judge_needs_the_quorum_first() {
+        let mut poll = governance_poll(Pubkey::new_unique());
+        poll.quorum = Some(Quorum::Votes { min_votes: 10 });
+        assert!(poll.judge(9, 9, 1) == PollOutcome::NoQuorum);
+        assert!(poll.judge(10, 6, 1) == PollOutcome::Passed);
+
+        // 20% of 1_000 eligible
+        poll.quorum = Some(Quorum::Share { eligible: 1_000, bps: 2_000 });
+        assert!(poll.judge(199, 199, 1) == PollOutcome::NoQuorum);
+        assert!(poll.judge(200, 150, 1) == PollOutcome::Passed);
+    }
+
+    #[test]
+    fn judge_fails_ties_and_empty_polls() {
+        let mut poll = governance_poll(Pubkey::new_unique());
+        poll.quorum = None;
+        assert!(poll.judge(0, 0, 0) == PollOutcome::Failed);
+        assert!(poll.judge(10, 5, 2) == PollOutcome::Failed);
+    }
+
+    #[test]
+    fn judge_applies_the_pass_threshold() {
+        let mut poll = governance_poll(Pubkey::new_unique());
+        poll.quorum = None;
+        poll.pass_threshold_bps = 6_000;
+        assert!(poll.judge(10, 5, 1) == PollOutcome::Failed);
+        assert!(poll.judge(10, 6, 1) == PollOutcome::Passed);
+    }
+}
@@ -9,10 +9,74 @@ export enum VoteError {
    CandidateNotRegistered = 'CandidateNotRegistered',
    PollNotActive = 'PollNotActive',
    PollDoesNotExist = 'PollDoesNotExist',
+   InvalidRegistrationDeadline = 'InvalidRegistrationDeadline',
+   RegistrationClosed = 'RegistrationClosed',
+   RegistrationNotAllowed = 'RegistrationNotAllowed',
+   NotPollCreator = 'NotPollCreator',
+   TokenAccountRequired = 'TokenAccountRequired',
+   InvalidTokenAccount = 'InvalidTokenAccount',
+   NoVotingPower = 'NoVotingPower',
+   EscrowRequired = 'EscrowRequired',
+   PollNotEnded = 'PollNotEnded',
+   WrongPollMode = 'WrongPollMode',
+   TooManyCandidates = 'TooManyCandidates',
+   InvalidBallot = 'InvalidBallot',
+   InvalidVoterAccount = 'InvalidVoterAccount',
+   TallyFinished = 'TallyFinished',
+   InvalidPollMode = 'InvalidPollMode',
+   InvalidCandidateAccount = 'InvalidCandidateAccount',
+   InsufficientCredits = 'InsufficientCredits',
+   RevealNotOpen = 'RevealNotOpen',
+   AlreadyRevealed = 'AlreadyRevealed',
+   CommitmentMismatch = 'CommitmentMismatch',
+   NotEligible = 'NotEligible',
+   PollAlreadyStarted = 'PollAlreadyStarted',
+   PollAlreadyFinalized = 'PollAlreadyFinalized',
+   IncompleteCandidates = 'IncompleteCandidates',
+   TallyNotFinished = 'TallyNotFinished',
+   SameCandidate = 'SameCandidate',
+   VoteLocked = 'VoteLocked',
+   SelfDelegation = 'SelfDelegation',
+   InvalidDelegation = 'InvalidDelegation',
+   DelegationNotAllowed = 'DelegationNotAllowed',
+   NotDelegated = 'NotDelegated',
+   DelegatedVote = 'DelegatedVote',
+   VoteHasDelegations = 'VoteHasDelegations',
+   InvalidPollRules = 'InvalidPollRules',
+   PollNotClosable = 'PollNotClosable',
+   CandidatesStillOpen = 'CandidatesStillOpen',
+   PollCancelled = 'PollCancelled',
+   InvalidExtension = 'InvalidExtension',
+   InvalidQuestion = 'InvalidQuestion',
+   TooManyQuestions = 'TooManyQuestions',
+   InvalidAnswers = 'InvalidAnswers',
+   NftRequired = 'NftRequired',
+   InvalidNft = 'InvalidNft',
+   ConflictingVoterGate = 'ConflictingVoterGate',
+   InvalidConvictionParams = 'InvalidConvictionParams',
+   ProposalExecuted = 'ProposalExecuted',
+   ConvictionBelowThreshold = 'ConvictionBelowThreshold',
+   InvalidStakeAmount = 'InvalidStakeAmount',
+   CandidatesRegistered = 'CandidatesRegistered',
+   NotCandidateAuthority = 'NotCandidateAuthority',
+   PollNotFinalized = 'PollNotFinalized',
+   NoDeposit = 'NoDeposit',
+   DepositNotSettled = 'DepositNotSettled',
+   InvalidProposalAction = 'InvalidProposalAction',
+   NotAProposal = 'NotAProposal',
+   ProposalNotPassed = 'ProposalNotPassed',
+   TimelockActive = 'TimelockActive',
+   ActionExecuted = 'ActionExecuted',
+   NotGovernancePoll = 'NotGovernancePoll',
+   CandidateNameTooLong = 'CandidateNameTooLong',
+   CandidateBioTooLong = 'CandidateBioTooLong',
+   CandidateImageUriTooLong = 'CandidateImageUriTooLong',
+   DescriptionTooLong = 'DescriptionTooLong',
 }
 
 export enum ChatError {
    MessageTooLong = 'MessageTooLong',
+   UnauthorizedThreadAccess = 'UnauthorizedThreadAccess',
 }
 
 export enum ErrorCode {
@@ -30,6 +94,19 @@ export enum ErrorCode {
    InsufficientFund = 'InsufficientFund',
    InvalidPlatformAddress = 'InvalidPlatformAddress',
    InvalidPlatformFee = 'InvalidPlatformFee',
+   ModulePaused = 'ModulePaused',
+   InvalidDeadline = 'InvalidDeadline',
+   CampaignEnded = 'CampaignEnded',
+   GoalNotReached = 'GoalNotReached',
+   RefundNotAvailable = 'RefundNotAvailable',
+   WrongCurrency = 'WrongCurrency',
+   InvalidMilestones = 'InvalidMilestones',
+   MilestoneLocked = 'MilestoneLocked',
+   MilestoneNotOpen = 'MilestoneNotOpen',
+   MilestoneVotingActive = 'MilestoneVotingActive',
+   InvalidRewardTier = 'InvalidRewardTier',
+   RewardTierSoldOut = 'RewardTierSoldOut',
+   InvalidGovernanceQuorum = 'InvalidGovernanceQuorum',
 }
 
 export enum TodoError {
@@ -59,6 +136,10 @@ export interface ProgramState {
    campaignCount: BN;
    platformFee: BN;
    platformAddress: PublicKey;
+   crowdfundingPaused: boolean;
+   blogPaused: boolean;
+   governanceMint: PublicKey | null;
+   governanceMinQuorumBps: number;
 }
 
 export interface Campaign {
@@ -74,6 +155,15 @@ export interface Campaign {
    withdrawals: BN;
    balance: BN;
    active: boolean;
+   poll: PublicKey | null;
+   deadline: BN;
+   fundingModel: FundingModel;
+   mint: PublicKey | null;
+   milestones: Milestone[];
+   withdrawn: BN;
+   refundPool: BN;
+   tiers: RewardTier[];
+   creatorDonated: BN;
 }
 
 export interface Transaction {
@@ -82,36 +172,75 @@ export interface Transaction {
    amount: BN;
    timestamp: BN;
    credited: boolean;
+   tier: number;
 }
 
 export interface Poll {
    id: BN;
+   creator: PublicKey;
    description: string;
    start: BN;
    end: BN;
    candidates: BN;
+   registrationPolicy: RegistrationPolicy;
+   registrationEnd: BN;
+   mode: PollMode;
+   voters: BN;
+   revealed: BN;
+   voterRoot: number[] | null;
+   weightedRoot: boolean;
+   finalized: boolean;
+   totalVotes: BN;
+   winners: BN[];
+   tie: boolean;
+   unrevealed: BN;
+   quorum: Quorum | null;
+   passThresholdBps: number;
+   outcome: PollOutcome;
+   closedCandidates: BN;
+   cancelled: boolean;
+   extended: BN;
+   nftCollection: PublicKey | null;
+   nominationDeposit: BN;
+   depositMinShareBps: number;
+   action: ProposalAction | null;
+   actionCid: BN;
+   actionExecuted: boolean;
+   finalizedAt: BN;
+   audience: PollAudience;
 }
 
 export interface Counter {
    count: BN;
 }
 
-export interface Registerations {
-   count: BN;
-}
-
 export interface Candidate {
    cid: BN;
    pollId: BN;
+   authority: PublicKey;
    name: string;
    votes: BN;
    hasRegistered: boolean;
+   bio: string;
+   imageUri: string;
+   deposit: BN;
+   withdrawn: boolean;
 }
 
 export interface Voter {
    cid: BN;
    pollId: BN;
    hasVoted: boolean;
+   weight: BN;
+   ballot: BN[];
+   talliedRound: number;
+   creditsSpent: BN;
+   allocations: QuadraticAllocation[];
+   commitment: number[];
+   revealed: boolean;
+   delegate: PublicKey | null;
+   delegatedWeight: BN;
+   rentPayer: PublicKey;
 }
 
 export interface UserAccount {
@@ -126,18 +255,92 @@ export interface PostAccount {
    id: number;
    title: string;
    content: string;
+   imageUrl: string;
    user: PublicKey;
    authority: PublicKey;
+   poll: PublicKey | null;
 }
 
 export interface MessageThread {
-   participantA: PublicKey;
-   participantB: PublicKey;
+   sender: PublicKey;
+   recipient: PublicKey;
 }
 
 export interface Message {
    sender: PublicKey;
-   timestamp: BN;
-   content: string;
    thread: PublicKey;
-}
\ No newline at end of file
+   content: string;
+   timestamp: BN;
+}
+
+export type FundingModel =
+   | { keepWhatYouRaise: {} }
+   | { allOrNothing: {} };
+
+export interface Milestone {
+   amount: BN;
+   description: string;
+   status: MilestoneStatus;
+   votingStart: BN;
+   votingEnd: BN;
+   eligibleWeight: BN;
+   approveWeight: BN;
+   rejectWeight: BN;
+}
+
+export type PollAudience =
+   | { anyone: {} }
+   | { followers: { author: PublicKey } }
+   | { donors: { cid: BN } };
+
+export type PollMode =
+   | { standard: {} }
+   | { tokenWeighted: { mint: PublicKey; escrow: boolean } }
+   | { rankedChoice: {} }
+   | { approval: { maxApprovals: number } }
+   | { quadratic: { credits: BN } }
+   | { commitReveal: { revealEnd: BN } }
+   | { survey: {} };
+
+export type PollOutcome =
+   | { pending: {} }
+   | { passed: {} }
+   | { failed: {} }
+   | { noQuorum: {} }
+   | { cancelled: {} };
+
+export type ProposalAction =
+   | { setPlatformFee: { fee: BN } }
+   | { setPlatformAddress: { address: PublicKey } }
+   | { setModulePaused: { module: PlatformModule; paused: boolean } };
+
+export interface QuadraticAllocation {
+   cid: BN;
+   votes: BN;
+}
+
+export type Quorum =
+   | { votes: { minVotes: BN } }
+   | { share: { eligible: BN; bps: number } };
+
+export type RegistrationPolicy =
+   | { creatorOnly: {} }
+   | { open: {} }
+   | { allowlist: {} };
+
+export interface RewardTier {
+   minAmount: BN;
+   quantity: BN;
+   claimed: BN;
+   description: string;
+}
+
+export type MilestoneStatus =
+   | { locked: {} }
+   | { voting: {} }
+   | { approved: {} }
+   | { rejected: {} };
+
+export type PlatformModule =
+   | { crowdfunding: {} }
+   | { blog: {} };
\ No newline at end of file
@@ -10,7 +10,7 @@ import { useWallet } from "@solana/wallet-adapter-react";
 import { WalletMultiButton } from "@solana/wallet-adapter-react-ui";
 import { BN } from "bn.js";
 import { Connection, SystemProgram, PublicKey } from '@solana/web3.js';
-import { getCounterAddress, getPollAddress, getRegistrationAddress, useProgram } from "@/utils/solana-program";
+import { DEFAULT_REGISTRATION_MINUTES, getCounterAddress, getPollAddress, getTallyAddress, useProgram } from "@/utils/solana-program";
 import { Button } from "@/components/ui/button";
 import { Card, CardHeader, CardTitle, CardContent } from "@/components/ui/card";
 import { DialogHeader } from "@/components/ui/dialog";
@@ -46,7 +46,7 @@ const VotingApp = () => {
     const [selectedPoll, setSelectedPoll] = useState<PollAccount | null>(null);
     const [filter, setFilter] = useState<"all" | "active" | "ended">("all");
     const [isDialogOpen, setIsDialogOpen] = useState(false);
-    const [newPoll, setNewPoll] = useState({ description: "", deadline: "" });
+    const [newPoll, setNewPoll] = useState({ description: "", deadline: "", registrationMinutes: String(DEFAULT_REGISTRATION_MINUTES) });
     const [newCandidateName, setNewCandidateName] = useState("");
 
 
@@ -90,7 +90,6 @@ const VotingApp = () => {
         try {
            
             const counterPda = getCounterAddress()
-            const registerationsPda = getRegistrationAddress()
         
 
             await program.methods
@@ -98,7 +97,6 @@ const VotingApp = () => {
                 .accounts({
                     user: wallet.publicKey,
                     counter: counterPda,
-                    registerations: registerationsPda,
                     systemProgram: SystemProgram.programId,
                 })
                 .rpc();
@@ -122,7 +120,18 @@ const VotingApp = () => {
 
         setIsLoading(true);
         try {
-               const deadlineTimestamp = new Date(newPoll.deadline).getTime() / 1000;
+        const registrationMinutes = Number(newPoll.registrationMinutes);
+        if (!Number.isInteger(registrationMinutes) || registrationMinutes < 1) {
+            toast({ title: "Error", description: "Candidate registration must last at least one minute.", variant: "destructive" });
+            return;
+        }
+               const deadlineTimestamp = Math.floor(new Date(newPoll.deadline).getTime() / 1000);
+        // voting opens once the candidate registration window has closed
+        const startTimestamp = Math.floor(Date.now() / 1000) + registrationMinutes * 60;
+        if (deadlineTimestamp <= startTimestamp) {
+            toast({ title: "Error", description: "Deadline must be after the candidate registration window.", variant: "destructive" });
+            return;
+        }
         const counterPda = getCounterAddress();
         const counterAccount = await program.account.counter.fetch(counterPda);
         const nextPollId = counterAccount.count.toNumber() + 1;
@@ -135,18 +144,22 @@ const VotingApp = () => {
                 // Add the nextPollId as the first argument
                 new BN(nextPollId), 
                 newPoll.description,
-                new BN(Date.now() / 1000),
-                new BN(deadlineTimestamp)
+                new BN(startTimestamp),
+                new BN(deadlineTimestamp),
+                { open: {} },
+                new BN(startTimestamp - 1),
+                { standard: {} }
             )
             .accounts({
                 user: wallet.publicKey,
                 poll: pollPda,
                 counter: counterPda,
+                tally: getTallyAddress(nextPollId),
                 systemProgram: SystemProgram.programId,
             })
             .rpc();
             await fetchBlockchainData();
-            setNewPoll({ description: "", deadline: "" });
+            setNewPoll({ description: "", deadline: "", registrationMinutes: String(DEFAULT_REGISTRATION_MINUTES) });
             setIsDialogOpen(false);
             toast({ title: "Success", description: "Poll created successfully!" });
         } catch (error) {
@@ -268,6 +281,17 @@ const VotingApp = () => {
                                                 min={new Date().toISOString().slice(0, 16)}
                                             />
                                         </div>
+                                        <div className="space-y-2">
+                                            <Label htmlFor="registrationMinutes">Candidate registration (minutes) *</Label>
+                                            <Input
+                                                id="registrationMinutes"
+                                                type="number"
+                                                min={1}
+                                                value={newPoll.registrationMinutes}
+                                                onChange={(e) => setNewPoll((prev) => ({ ...prev, registrationMinutes: e.target.value }))}
+                                            />
+                                            <p className="text-xs text-muted-foreground">Voting opens once registration closes.</p>
+                                        </div>
                                         <Button onClick={handleCreatePoll} className="w-full" disabled={isLoading}>
                                             {isLoading ? "Creating..." : "Create Poll"}
                                         </Button>
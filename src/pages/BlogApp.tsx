@@ -15,6 +15,7 @@ import {
 import { useToast } from "@/hooks/use-toast";
 import {
   getPostAddress,
+  getProgramStateAddress,
   getUserAddress,
   useProgram,
 } from "@/utils/solana-program";
@@ -163,6 +164,7 @@ const BlogApp = () => {
         .createPost(newPost.title.trim(), newPost.content.trim(), newPost.imageUrl || "")
         .accounts({
           postAccount: postPDA,
+          programState: getProgramStateAddress(),
           userAccount: userPDA,
           authority: wallet.publicKey,
           systemProgram: SystemProgram.programId,
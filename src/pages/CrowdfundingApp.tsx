@@ -14,6 +14,7 @@ import {
   getCampaignAddress,
   getDonorTransactionAddress,
   getWithdrawTransactionAddress, // Import the new function for withdrawal transactions
+  getTreasuryAddress,
   useProgram,
 } from "@/utils/solana-program"; // Make sure to update your utils file
 import { Card, CardHeader, CardTitle, CardContent } from "@/components/ui/card";
@@ -111,15 +112,23 @@ const CrowdfundingApp = () => {
       const newCampaignId = programState.campaignCount.toNumber() + 1;
       const campaignPda = getCampaignAddress(newCampaignId);
       
+      // SOL campaign with no deadline, no milestones, keeping whatever it raises
       await program.methods.createCampaign(
         newCampaign.title,
         newCampaign.description,
         newCampaign.imageUrl,
-        goalInLamports
+        goalInLamports,
+        new BN(0),
+        { keepWhatYouRaise: {} },
+        []
       )
         .accounts({
           programState: getProgramStateAddress(),
           campaign: campaignPda,
+          // SOL campaign: no token accounts
+          mint: null,
+          vault: null,
+          tokenProgram: null,
           creator: wallet.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -144,11 +153,13 @@ const CrowdfundingApp = () => {
       const amountInLamports = new BN(amt * anchor.web3.LAMPORTS_PER_SOL);
       const transactionPda = getDonorTransactionAddress(wallet.publicKey, campaignId, campaign.donors.toNumber() + 1);
 
+      // tier 0: no reward tier
       await program.methods
-        .donate(new BN(campaignId), amountInLamports)
+        .donate(new BN(campaignId), amountInLamports, 0)
         .accounts({
           campaign: getCampaignAddress(campaignId),
           transaction: transactionPda,
+          programState: getProgramStateAddress(),
           donor: wallet.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -187,6 +198,7 @@ const CrowdfundingApp = () => {
           systemProgram: SystemProgram.programId,
           programState: getProgramStateAddress(),
           platformAddress: programState.platformAddress, // Pass the platform address from state
+          treasury: getTreasuryAddress(),
         })
         .rpc();
       
@@ -7,7 +7,7 @@ import { useWallet } from "@solana/wallet-adapter-react";
 import { WalletMultiButton } from "@solana/wallet-adapter-react-ui";
 import { BN } from "@coral-xyz/anchor";
 import { SystemProgram, PublicKey } from '@solana/web3.js';
-import { getCounterAddress, getPollAddress, getRegistrationAddress, getCandidateAddress, getVoterAddress, useProgram } from "@/utils/solana-program";
+import { DEFAULT_REGISTRATION_MINUTES, getCounterAddress, getPollAddress, getTallyAddress, getCandidateAddress, getVoterAddress, useProgram } from "@/utils/solana-program";
 import { Button } from "@/components/ui/button";
 import { Card, CardHeader, CardTitle, CardContent } from "@/components/ui/card";
 import { Dialog, DialogTrigger, DialogContent, DialogTitle } from "@radix-ui/react-dialog";
@@ -49,7 +49,7 @@ const VotingApp = () => {
   const [selectedPoll, setSelectedPoll] = useState<PollAccount | null>(null);
   const [filter, setFilter] = useState<"all" | "active" | "ended">("all");
   const [isDialogOpen, setIsDialogOpen] = useState(false);
-  const [newPoll, setNewPoll] = useState({ description: "", deadline: "" });
+  const [newPoll, setNewPoll] = useState({ description: "", deadline: "", registrationMinutes: String(DEFAULT_REGISTRATION_MINUTES) });
   const [newCandidateName, setNewCandidateName] = useState("");
   const [hasVoted, setHasVoted] = useState(false);
 
@@ -125,14 +125,12 @@ console.log("formated candidate and poll",polls , candidates)
     setIsLoading(true);
     try {
       const counterPda = getCounterAddress();
-      const registrationsPda = getRegistrationAddress();
 
       await program.methods
         .initializeVote()
         .accounts({
           user: wallet.publicKey,
           counter: counterPda,
-          registerations: registrationsPda,
           systemProgram: SystemProgram.programId,
         })
         .rpc();
@@ -155,7 +153,18 @@ console.log("formated candidate and poll",polls , candidates)
 
     setIsLoading(true);
     try {
-      const deadlineTimestamp = new Date(newPoll.deadline).getTime() / 1000;
+      const registrationMinutes = Number(newPoll.registrationMinutes);
+      if (!Number.isInteger(registrationMinutes) || registrationMinutes < 1) {
+        toast({ title: "Error", description: "Candidate registration must last at least one minute.", variant: "destructive" });
+        return;
+      }
+      const deadlineTimestamp = Math.floor(new Date(newPoll.deadline).getTime() / 1000);
+      // voting opens once the candidate registration window has closed
+      const startTimestamp = Math.floor(Date.now() / 1000) + registrationMinutes * 60;
+      if (deadlineTimestamp <= startTimestamp) {
+        toast({ title: "Error", description: "Deadline must be after the candidate registration window.", variant: "destructive" });
+        return;
+      }
       const counterPda = getCounterAddress();
       const counterAccount = await program.account.counter.fetch(counterPda);
       const nextPollId = counterAccount.count.toNumber() + 1;
@@ -166,18 +175,22 @@ console.log("formated candidate and poll",polls , candidates)
         .createPoll(
           new BN(nextPollId),
           newPoll.description,
-          new BN(Date.now() / 1000),
-          new BN(deadlineTimestamp)
+          new BN(startTimestamp),
+          new BN(deadlineTimestamp),
+          { open: {} },
+          new BN(startTimestamp - 1),
+          { standard: {} }
         )
         .accounts({
           user: wallet.publicKey,
           poll: pollPda,
           counter: counterPda,
+          tally: getTallyAddress(nextPollId),
           systemProgram: SystemProgram.programId,
         })
         .rpc();
       await fetchBlockchainData();
-      setNewPoll({ description: "", deadline: "" });
+      setNewPoll({ description: "", deadline: "", registrationMinutes: String(DEFAULT_REGISTRATION_MINUTES) });
       setIsDialogOpen(false);
       toast({ title: "Success", description: "Poll created successfully!" });
     } catch (error) {
@@ -193,9 +206,8 @@ console.log("formated candidate and poll",polls , candidates)
     setIsLoading(true);
     try {
       const pollPda = getPollAddress(selectedPoll.id);
-      const registrationPda = getRegistrationAddress();
-      const registrationAccount = await program.account.registerations.fetch(registrationPda);
-      const nextCandidateId = registrationAccount.count.toNumber() + 1;
+      const pollAccount = await program.account.poll.fetch(pollPda);
+      const nextCandidateId = pollAccount.candidates.toNumber() + 1;
       const candidatePda = getCandidateAddress(selectedPoll.id, nextCandidateId);
 
       await program.methods
@@ -204,7 +216,7 @@ console.log("formated candidate and poll",polls , candidates)
           user: wallet.publicKey,
           poll: pollPda,
           candidate: candidatePda,
-          registerations: registrationPda,
+          registrationPass: null,
           systemProgram: SystemProgram.programId,
         })
         .rpc();
@@ -228,12 +240,18 @@ console.log("formated candidate and poll",polls , candidates)
       const voterPda = getVoterAddress(selectedPoll.id, wallet.publicKey);
 
       await program.methods
-        .vote(new BN(selectedPoll.id), new BN(cid))
+        .vote(new BN(selectedPoll.id), new BN(cid), null)
         .accounts({
           user: wallet.publicKey,
           poll: pollPda,
+          tally: getTallyAddress(selectedPoll.id),
           candidate: candidatePda,
           voter: voterPda,
+          mint: null,
+          voterTokenAccount: null,
+          voteEscrow: null,
+          tokenProgram: null,
+          audienceRecord: null,
           systemProgram: SystemProgram.programId,
         })
         .rpc();
@@ -407,6 +425,17 @@ const isOwner = new PublicKey(poll.creator).equals(wallet.publicKey);
                       min={new Date().toISOString().slice(0, 16)}
                     />
                   </div>
+                  <div className="space-y-2">
+                    <label htmlFor="registrationMinutes" className="text-sm font-medium">Candidate registration (minutes) *</label>
+                    <Input
+                      id="registrationMinutes"
+                      type="number"
+                      min={1}
+                      value={newPoll.registrationMinutes}
+                      onChange={(e) => setNewPoll((prev) => ({ ...prev, registrationMinutes: e.target.value }))}
+                    />
+                    <p className="text-xs text-muted-foreground">Voting opens once registration closes.</p>
+                  </div>
                   <Button onClick={handleCreatePoll} className="w-full" disabled={isLoading}>
                     {isLoading ? "Creating..." : "Create Poll"}
                   </Button>
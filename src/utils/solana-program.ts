@@ -59,6 +59,15 @@ export const getProgramStateAddress = (): web3.PublicKey => {
   return pda;
 };
 
+// Returns the PDA for the conviction treasury, which takes a share of withdrawal fees.
+export const getTreasuryAddress = (): web3.PublicKey => {
+  const [pda] = web3.PublicKey.findProgramAddressSync(
+    [utils.bytes.utf8.encode('treasury')],
+    programID
+  );
+  return pda;
+};
+
 // Returns the PDA for a specific campaign.
 export const getCampaignAddress = (campaignId: number): PublicKey => {
   // Input validation
@@ -186,16 +195,19 @@ export const getCounterAddress = () => {
   )[0];
 };
 
-export const getRegistrationAddress = () => {
+// Default time, in minutes, candidates get to register before voting starts
+export const DEFAULT_REGISTRATION_MINUTES = 60;
+
+export const getPollAddress = (pollId: number) => {
   return PublicKey.findProgramAddressSync(
-    [Buffer.from("registerations")],
+    [Buffer.from("poll"), new BN(pollId).toArrayLike(Buffer, "le", 8)],
    programID // Replace with your actual program ID
   )[0];
 };
 
-export const getPollAddress = (pollId: number) => {
+export const getTallyAddress = (pollId: number) => {
   return PublicKey.findProgramAddressSync(
-    [Buffer.from("poll"), new BN(pollId).toArrayLike(Buffer, "le", 8)],
+    [Buffer.from("tally"), new BN(pollId).toArrayLike(Buffer, "le", 8)],
    programID // Replace with your actual program ID
   )[0];
 };
@@ -203,6 +215,7 @@ export const getPollAddress = (pollId: number) => {
 export const getCandidateAddress = (pollId: number, candidateId: number) => {
   return PublicKey.findProgramAddressSync(
     [
+      Buffer.from("candidate"),
       new BN(pollId).toArrayLike(Buffer, "le", 8),
       new BN(candidateId).toArrayLike(Buffer, "le", 8),
     ],
@@ -242,11 +255,13 @@ export const donateToCampaign = async (
     const campaign = await getCampaign(program, cid);
     const transactionPda = getDonorTransactionAddress(wallet.publicKey, cid, campaign.donors.toNumber() + 1);
 
+    // tier 0: no reward tier
     return await program.methods
-      .donate(new BN(cid), amountInLamports)
+      .donate(new BN(cid), amountInLamports, 0)
       .accounts({
         campaign: getCampaignAddress(cid),
         transaction: transactionPda,
+        programState: getProgramStateAddress(),
         donor: wallet.publicKey,
         systemProgram: SystemProgram.programId,
       })
@@ -275,6 +290,7 @@ export const withdrawFromCampaign = async (
         systemProgram: SystemProgram.programId,
         programState: getProgramStateAddress(),
         platformAddress,
+        treasury: getTreasuryAddress(),
       })
       .rpc();
 };